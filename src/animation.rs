@@ -0,0 +1,175 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use tui_blackjack::{Card, Event, Seat};
+
+const DEAL: Duration = Duration::from_millis(250);
+const FLIP: Duration = Duration::from_millis(300);
+const CHIPS: Duration = Duration::from_millis(450);
+
+#[derive(Debug, Clone)]
+pub enum Animation {
+    /// A card sliding from the shoe to a seat
+    Deal { to: Seat, card: Card },
+    /// The dealer's hole card turning face up
+    Flip { card: Card },
+    /// Winnings moving to the bank (positive) or a lost stake moving to the dealer (negative)
+    Chips { change: i64 },
+}
+
+impl Animation {
+    fn duration(&self) -> Duration {
+        match self {
+            Animation::Deal { .. } => DEAL,
+            Animation::Flip { .. } => FLIP,
+            Animation::Chips { .. } => CHIPS,
+        }
+    }
+}
+
+/// Plays the engine's event stream back as animations, one after another. Until an animation
+/// has finished the UI keeps drawing the table as it was before the event.
+#[derive(Debug)]
+pub struct Animator {
+    enabled: bool,
+    /// How far into `App::events` has been read
+    cursor: usize,
+    queue: VecDeque<Animation>,
+    current: Option<(Animation, Instant)>,
+}
+
+impl Animator {
+    pub fn new(enabled: bool) -> Self {
+        Animator {
+            enabled,
+            cursor: 0,
+            queue: VecDeque::new(),
+            current: None,
+        }
+    }
+
+    /// Queue animations for events emitted since the last update and start the next animation
+    /// once the current one has finished
+    pub fn update(&mut self, events: &[Event]) {
+        for event in &events[self.cursor..] {
+            let animation = match event {
+                Event::CardDealt { to, card } => Animation::Deal {
+                    to: *to,
+                    card: card.clone(),
+                },
+                Event::HoleCardRevealed { card } => Animation::Flip { card: card.clone() },
+                Event::BankChanged { change, .. } => Animation::Chips { change: *change },
+                Event::RoundReset => {
+                    self.skip();
+                    continue;
+                }
+                _ => continue,
+            };
+            if self.enabled {
+                self.queue.push_back(animation);
+            }
+        }
+        self.cursor = events.len();
+
+        if let Some((animation, start)) = &self.current {
+            if start.elapsed() >= animation.duration() {
+                self.current = None;
+            }
+        }
+        if self.current.is_none() {
+            self.current = self
+                .queue
+                .pop_front()
+                .map(|animation| (animation, Instant::now()));
+        }
+    }
+
+    /// Finish every queued animation at once
+    pub fn skip(&mut self) {
+        self.queue.clear();
+        self.current = None;
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.current.is_none() && self.queue.is_empty()
+    }
+
+    /// The animation playing now and how far through it is, from 0 to 1
+    pub fn current(&self) -> Option<(&Animation, f32)> {
+        self.current.as_ref().map(|(animation, start)| {
+            let progress = start.elapsed().as_secs_f32() / animation.duration().as_secs_f32();
+            (animation, progress.min(1.0))
+        })
+    }
+
+    fn pending(&self) -> impl Iterator<Item = &Animation> {
+        self.current
+            .iter()
+            .map(|(animation, _)| animation)
+            .chain(self.queue.iter())
+    }
+
+    /// Cards dealt to a seat that have not landed yet. They are always the last cards of the hand.
+    pub fn cards_in_flight(&self, seat: Seat) -> usize {
+        self.pending()
+            .filter(|animation| matches!(animation, Animation::Deal { to, .. } if *to == seat))
+            .count()
+    }
+
+    /// The hole card has not yet turned past halfway
+    pub fn hole_card_hidden(&self) -> bool {
+        let flipping =
+            matches!(self.current(), Some((Animation::Flip { .. }, progress)) if progress < 0.5);
+        flipping
+            || self
+                .queue
+                .iter()
+                .any(|animation| matches!(animation, Animation::Flip { .. }))
+    }
+
+    /// Change to the bank still on its way
+    pub fn bank_in_flight(&self) -> i64 {
+        self.pending()
+            .map(|animation| match animation {
+                Animation::Chips { change } => *change,
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tui_blackjack::{App, Command, GameState, Rules};
+
+    #[test]
+    fn follows_event_stream() {
+        // Seed 17 deals a hand the player can stand on
+        let mut app = App::new(100, Rules::default(), 17);
+        let mut animator = Animator::new(true);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        animator.update(app.events());
+        assert_eq!(2, animator.cards_in_flight(Seat::Player(0)));
+        assert_eq!(2, animator.cards_in_flight(Seat::Dealer));
+
+        app.run(Command::Stand).unwrap();
+        while let GameState::DealerTurn = app.state {
+            app.run(Command::AdvanceDealer).unwrap();
+        }
+        animator.update(app.events());
+        assert!(animator.hole_card_hidden());
+        assert_eq!(app.bank as i64 - 100, animator.bank_in_flight());
+
+        animator.skip();
+        assert!(animator.is_idle());
+        assert!(!animator.hole_card_hidden());
+
+        let mut animator = Animator::new(false);
+        animator.update(app.events());
+        assert!(animator.is_idle());
+    }
+}
@@ -1,179 +1,931 @@
+use std::{error::Error, fmt::Display};
+
 use crate::cards::*;
+use crate::event::*;
+use crate::hand::*;
+use crate::rules::*;
 
-const ACE_HIGH: u8 = 11;
-const ACE_LOW: u8 = 1;
-const BLACKJACK: u8 = 21;
-const FACECARD: u8 = 10;
 const DEALER_STAND: u8 = 17;
+pub const DEFAULT_BANK: u32 = 100;
 
 #[derive(Debug)]
 pub struct App {
     pub bank: u32,
-    pub player_hand: Vec<Card>,
+    pub hands: Vec<Hand>,
+    /// Index into `hands` of the hand currently being played
+    pub active_hand: usize,
     pub dealer_hand: Vec<Card>,
     pub current_bet: u32,
     pub blackjack_payout: u32,
     pub state: GameState,
+    pub shoe: Shoe,
+    pub rules: Rules,
+    /// Set when the shoe was reshuffled during or at the end of the last round, until the next bet
+    pub shuffled: bool,
+    /// Insurance staked against a dealer blackjack this round
+    pub insurance: u32,
+    /// Set once the insurance bet has been resolved
+    pub insurance_outcome: Option<Outcome>,
+    /// The dealer has yet to check the hole card, leaving room for insurance and early surrender
+    peek_pending: bool,
+    events: Vec<Event>,
 }
 
 impl App {
-    pub fn new(bank: u32) -> Self {
-        let player_hand = Vec::new();
+    /// Create a table with the given rules, dealing from a shoe shuffled with `seed`
+    pub fn new(bank: u32, rules: Rules, seed: u64) -> Self {
+        let hands = Vec::new();
         let dealer_hand = Vec::new();
+        let shoe = Shoe::new(rules.decks, rules.penetration, seed);
 
         App {
             bank,
-            player_hand,
+            hands,
+            active_hand: 0,
             dealer_hand,
             current_bet: 0,
             blackjack_payout: 0,
             state: GameState::EnterBet,
+            shoe,
+            rules,
+            shuffled: false,
+            insurance: 0,
+            insurance_outcome: None,
+            peek_pending: false,
+            events: Vec::new(),
         }
     }
 
-    pub fn place_bet(&mut self, bet: u32) {
-        self.current_bet = bet;
-        self.blackjack_payout = bet * 3 / 2;
-        self.state = GameState::PlayerTurn;
+    /// Rebuild a table by applying recorded events to a fresh one. A card is drawn from the shoe
+    /// for every card dealt and the shoe reshuffled for every shuffle, so a stream recorded from
+    /// the same seed carries on with the same cards.
+    pub fn replay(
+        bank: u32,
+        rules: Rules,
+        seed: u64,
+        events: &[Event],
+    ) -> Result<Self, ReplayError> {
+        let mut app = App::new(bank, rules, seed);
+        for (position, event) in events.iter().enumerate() {
+            // Dealing to a player may open the next hand; every other event needs the hand to exist
+            let (hand, open) = match *event {
+                Event::CardDealt {
+                    to: Seat::Player(hand),
+                    ..
+                } => (hand, app.hands.len() + 1),
+                Event::HandSplit { hand }
+                | Event::BetDoubled { hand }
+                | Event::HandActivated { hand }
+                | Event::HandSettled { hand, .. } => (hand, app.hands.len()),
+                _ => (0, 1),
+            };
+            if hand >= open {
+                return Err(ReplayError::UnknownHand { position, hand });
+            }
+            match event {
+                Event::CardDealt { .. } => {
+                    app.shoe.draw();
+                }
+                Event::Shuffle => app.shoe.shuffle(),
+                _ => {}
+            }
+            app.emit(event.clone());
+        }
+        // A peek that finds no natural leaves no event, so work out whether it is still to come
+        app.peek_pending = match app.state {
+            GameState::Insurance => true,
+            GameState::PlayerTurn => {
+                app.rules.surrender == SurrenderRule::Early
+                    && app.dealer_may_have_blackjack()
+                    && app.hands.len() == 1
+                    && app.current_hand().cards.len() == 2
+                    && !app.current_hand().is_natural()
+            }
+            _ => false,
+        };
+        Ok(app)
+    }
+
+    /// Every event emitted since the table opened, oldest first. Consumers keep track of how far
+    /// into the stream they have read.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Events emitted since the table was last reset
+    pub fn round_events(&self) -> &[Event] {
+        let start = self
+            .events
+            .iter()
+            .rposition(|event| *event == Event::RoundReset)
+            .map_or(0, |index| index + 1);
+        &self.events[start..]
     }
 
-    pub fn start(&mut self) {
-        self.player_hand = vec![Card::new(), Card::new()];
-        self.dealer_hand = vec![Card::new(), Card::new()];
-        self.dealer_hand[0].face_down();
-        if calc_hand_score(&self.player_hand) == BLACKJACK {
-            self.state = GameState::Blackjack;
-            self.bank += self.blackjack_payout;
+    /// Record an event and apply it to the table
+    fn emit(&mut self, event: Event) {
+        self.apply(&event);
+        self.events.push(event);
+    }
+
+    fn apply(&mut self, event: &Event) {
+        match event {
+            Event::RoundReset => {
+                self.current_bet = 0;
+                self.blackjack_payout = 0;
+                self.insurance = 0;
+                self.insurance_outcome = None;
+                self.hands.clear();
+                self.active_hand = 0;
+                self.dealer_hand.clear();
+                self.state = GameState::EnterBet;
+            }
+            Event::Shuffle => self.shuffled = true,
+            Event::BetPlaced { bet } => {
+                self.shuffled = false;
+                self.current_bet = *bet;
+                self.blackjack_payout = self.rules.blackjack_payout.pay(*bet);
+            }
+            Event::CardDealt {
+                to: Seat::Dealer,
+                card,
+            } => self.dealer_hand.push(card.clone()),
+            Event::CardDealt {
+                to: Seat::Player(index),
+                card,
+            } => match self.hands.get_mut(*index) {
+                Some(hand) => hand.cards.push(card.clone()),
+                None => self
+                    .hands
+                    .push(Hand::new(vec![card.clone()], self.current_bet)),
+            },
+            Event::HoleCardRevealed { card } => {
+                if let Some(hole_card) = self.dealer_hand.get_mut(1) {
+                    *hole_card = card.clone();
+                }
+            }
+            Event::InsurancePlaced { stake } => self.insurance = *stake,
+            Event::HandSplit { hand } => {
+                let split = &mut self.hands[*hand];
+                split.split = true;
+                if let Some(card) = split.cards.pop() {
+                    let mut split_hand = Hand::new(vec![card], self.current_bet);
+                    split_hand.split = true;
+                    self.hands.insert(hand + 1, split_hand);
+                }
+            }
+            Event::BetDoubled { hand } => self.hands[*hand].bet *= 2,
+            Event::HandActivated { hand } => self.active_hand = *hand,
+            Event::HandSettled { hand, outcome, .. } => self.hands[*hand].outcome = Some(*outcome),
+            Event::InsuranceSettled { outcome, .. } => self.insurance_outcome = Some(*outcome),
+            Event::BankChanged { bank, .. } => self.bank = *bank,
+            Event::StateChanged { state } => self.state = *state,
+        }
+    }
+
+    fn set_state(&mut self, state: GameState) {
+        self.emit(Event::StateChanged { state });
+    }
+
+    /// Draw the next card from the shoe for a seat
+    fn deal(&mut self, to: Seat) {
+        let card = self.draw();
+        self.emit(Event::CardDealt { to, card });
+    }
+
+    /// Take the next card from the shoe, reshuffling first if a long round has run it dry
+    fn draw(&mut self) -> Card {
+        if self.shoe.remaining() == 0 {
+            self.shoe.shuffle();
+            self.emit(Event::Shuffle);
+        }
+        self.shoe.draw()
+    }
+
+    fn change_bank(&mut self, change: i64) {
+        if change != 0 {
+            let bank = (self.bank as i64 + change) as u32;
+            self.emit(Event::BankChanged { bank, change });
+        }
+    }
+
+    /// Stake the bet for the next round, within the table limits and the bank
+    pub fn place_bet(&mut self, bet: u32) -> Result<(), GameError> {
+        if self.state != GameState::EnterBet {
+            return Err(GameError::RoundInProgress);
+        }
+        let (min, max) = (self.rules.min_bet, self.rules.max_bet);
+        if !(min..=max).contains(&bet) {
+            return Err(GameError::BetOutOfLimits { bet, min, max });
+        }
+        if bet > self.bank {
+            return Err(GameError::InsufficientFunds {
+                needed: bet,
+                available: self.bank,
+            });
+        }
+        self.emit(Event::BetPlaced { bet });
+        Ok(())
+    }
+
+    /// Deal a new round for the bet already placed
+    pub fn start(&mut self) -> Result<(), GameError> {
+        if self.state != GameState::EnterBet || !self.hands.is_empty() {
+            return Err(GameError::RoundInProgress);
+        }
+        if self.current_bet == 0 {
+            return Err(GameError::NoBet);
+        }
+        // Deal in casino order: player, dealer upcard, player, dealer hole card
+        self.deal(Seat::Player(0));
+        self.deal(Seat::Dealer);
+        self.deal(Seat::Player(0));
+        if !self.rules.no_hole_card {
+            let mut card = self.draw();
+            card.face_down();
+            self.emit(Event::CardDealt {
+                to: Seat::Dealer,
+                card,
+            });
+        }
+        self.peek_pending = self.dealer_may_have_blackjack();
+        if self.dealer_hand[0].rank == Rank::Ace {
+            self.set_state(GameState::Insurance);
         } else {
-            self.state = GameState::PlayerTurn;
+            self.begin_play();
         }
+        Ok(())
     }
 
-    pub fn reset(&mut self) {
-        self.current_bet = 0;
-        self.player_hand.clear();
-        self.dealer_hand.clear();
-        self.state = GameState::EnterBet;
+    /// Hand control to the player. Early surrender must be offered before the dealer checks for
+    /// blackjack, otherwise the dealer peeks straight away.
+    fn begin_play(&mut self) {
+        self.set_state(GameState::PlayerTurn);
+        let early_surrender = self.rules.surrender == SurrenderRule::Early;
+        if !(early_surrender && self.peek_pending && !self.current_hand().is_natural()) {
+            self.peek();
+        }
+    }
+
+    /// Clear the table for the next bet once the round is over, reshuffling if the cut card came
+    /// out
+    pub fn reset(&mut self) -> Result<(), GameError> {
+        if matches!(
+            self.state,
+            GameState::Insurance | GameState::PlayerTurn | GameState::DealerTurn
+        ) {
+            return Err(GameError::RoundInProgress);
+        }
+        self.emit(Event::RoundReset);
+        if self.shoe.cut_card_reached() {
+            self.shoe.shuffle();
+            self.emit(Event::Shuffle);
+        }
+        Ok(())
+    }
+
+    /// Hand currently being played, or `None` before the cards are dealt
+    pub fn player_hand(&self) -> Option<&Hand> {
+        self.hands.get(self.active_hand)
+    }
+
+    /// The hand being played, for use once the round has been dealt. Panics before that.
+    fn current_hand(&self) -> &Hand {
+        &self.hands[self.active_hand]
+    }
+
+    /// Value of the hand being played; zero before the cards are dealt
+    pub fn player_value(&self) -> HandValue {
+        self.player_hand()
+            .map_or_else(|| HandValue::of(&[]), Hand::value)
     }
 
     pub fn player_score(&self) -> u8 {
-        calc_hand_score(&self.player_hand)
+        self.player_value().total
     }
 
     pub fn dealer_showing(&self) -> u8 {
-        calc_card_score(&self.dealer_hand[1])
+        card_value(&self.dealer_hand[0])
+    }
+
+    /// Dealer draws below 17, and on soft 17 when the rules say so
+    pub fn dealer_must_hit(&self) -> bool {
+        let value = self.dealer_value();
+        value.total < DEALER_STAND
+            || (value.total == DEALER_STAND && value.is_soft && self.rules.dealer_hits_soft_17)
+    }
+
+    /// The dealer's second card is face down or has not been dealt yet
+    pub fn hole_card_hidden(&self) -> bool {
+        self.dealer_hand.get(1).is_none_or(|card| card.down)
+    }
+
+    /// Value of the whole dealer hand, including a face-down hole card
+    pub fn dealer_value(&self) -> HandValue {
+        HandValue::of(&self.dealer_hand)
     }
 
     pub fn dealer_score(&self) -> u8 {
-        calc_hand_score(&self.dealer_hand)
+        self.dealer_value().total
+    }
+
+    /// Total amount wagered across all hands this round
+    pub fn total_bet(&self) -> u32 {
+        self.hands.iter().map(|hand| hand.bet).sum()
+    }
+
+    /// Net amount won (positive) or lost (negative) over all settled hands and insurance this
+    /// round
+    pub fn net_result(&self) -> i64 {
+        let hands: i64 = self
+            .hands
+            .iter()
+            .map(|hand| match hand.outcome {
+                Some(outcome) => self.payout(hand, outcome),
+                None => 0,
+            })
+            .sum();
+        hands + self.insurance_result()
+    }
+
+    /// Amount won or lost on insurance, paid at 2:1 against a dealer blackjack
+    pub fn insurance_result(&self) -> i64 {
+        match self.insurance_outcome {
+            Some(Outcome::Win) => 2 * self.insurance as i64,
+            Some(_) => -(self.insurance as i64),
+            None => 0,
+        }
+    }
+
+    /// Largest insurance bet allowed: half the original bet, as far as the bank covers it
+    pub fn max_insurance(&self) -> u32 {
+        (self.current_bet / 2).min(self.bank.saturating_sub(self.committed()))
+    }
+
+    /// Total amount at risk on hands and insurance
+    fn committed(&self) -> u32 {
+        self.total_bet() + self.insurance
+    }
+
+    /// The active hand may be split under the table rules and the bank covers a second bet
+    pub fn can_split(&self) -> bool {
+        self.check(Command::Split).is_ok()
+    }
+
+    /// The active hand may be doubled under the table rules and the bank covers the extra bet
+    pub fn can_double(&self) -> bool {
+        self.check(Command::Double).is_ok()
+    }
+
+    pub fn can_surrender(&self) -> bool {
+        self.check(Command::Surrender).is_ok()
     }
 
-    pub fn run(&mut self, command: Command) {
+    fn split_allowed(&self) -> bool {
+        let hand = self.current_hand();
+        if hand.cards.len() != 2 || self.hands.len() >= self.rules.max_hands as usize {
+            return false;
+        }
+        if hand.is_split_aces() && !self.rules.resplit_aces {
+            return false;
+        }
+        let unlike_tens = self.rules.split_unlike_tens
+            && hand.cards.iter().all(|card| card_value(card) == FACECARD);
+        hand.is_pair() || unlike_tens
+    }
+
+    fn double_allowed(&self) -> bool {
+        let hand = self.current_hand();
+        if hand.cards.len() != 2 || self.hand_locked() {
+            return false;
+        }
+        if hand.split && !self.rules.double_after_split {
+            return false;
+        }
+        self.rules.double.allows(hand.value().total)
+    }
+
+    /// Surrender is offered on the first two cards of the original hand. Late surrender comes
+    /// after the dealer has peeked, so a dealer natural has already ended the round.
+    fn surrender_allowed(&self) -> bool {
+        let hand = self.current_hand();
+        self.rules.surrender != SurrenderRule::None
+            && self.hands.len() == 1
+            && hand.cards.len() == 2
+    }
+
+    /// The bank covers another `amount` on top of everything already at risk
+    fn check_funds(&self, amount: u32) -> Result<(), GameError> {
+        let available = self.bank.saturating_sub(self.committed());
+        if amount > available {
+            return Err(GameError::InsufficientFunds {
+                needed: amount,
+                available,
+            });
+        }
+        Ok(())
+    }
+
+    /// Commands the player may issue right now given the hand, bank and rules. Insurance is
+    /// listed with the largest stake allowed; a stake of zero declines it.
+    pub fn legal_actions(&self) -> Vec<Command> {
+        let candidates = match self.state {
+            GameState::Insurance => {
+                vec![Command::Insurance(self.max_insurance()), Command::EvenMoney]
+            }
+            GameState::PlayerTurn => vec![
+                Command::Hit,
+                Command::Stand,
+                Command::Double,
+                Command::Split,
+                Command::Surrender,
+            ],
+            GameState::DealerTurn => vec![Command::AdvanceDealer],
+            _ => Vec::new(),
+        };
+        candidates
+            .into_iter()
+            .filter(|command| self.check(*command).is_ok())
+            .collect()
+    }
+
+    /// Check whether a command may be played right now, without playing it
+    pub fn check(&self, command: Command) -> Result<(), GameError> {
+        let expected = match command {
+            Command::Insurance(_) | Command::EvenMoney => GameState::Insurance,
+            Command::AdvanceDealer => GameState::DealerTurn,
+            _ => GameState::PlayerTurn,
+        };
+        if self.state != expected {
+            return Err(GameError::IllegalAction {
+                command,
+                state: self.state,
+            });
+        }
+        let allowed = match command {
+            Command::Hit => !self.hand_locked(),
+            Command::Double => self.double_allowed(),
+            Command::Split => self.split_allowed(),
+            Command::Surrender => self.surrender_allowed(),
+            Command::EvenMoney => self.current_hand().is_natural(),
+            Command::Stand | Command::Insurance(_) | Command::AdvanceDealer => true,
+        };
+        if !allowed {
+            return Err(GameError::NotAllowed(command));
+        }
+        match command {
+            Command::Double => self.check_funds(self.current_hand().bet),
+            Command::Split => self.check_funds(self.current_bet),
+            Command::Insurance(stake) if stake > self.current_bet / 2 => {
+                Err(GameError::BetOutOfLimits {
+                    bet: stake,
+                    min: 0,
+                    max: self.current_bet / 2,
+                })
+            }
+            Command::Insurance(stake) => self.check_funds(stake),
+            _ => Ok(()),
+        }
+    }
+
+    fn dealer_has_blackjack(&self) -> bool {
+        self.dealer_value().is_blackjack
+    }
+
+    /// The dealer's upcard is an ace or ten-value card
+    fn dealer_may_have_blackjack(&self) -> bool {
+        matches!(self.dealer_showing(), ACE_HIGH | FACECARD)
+    }
+
+    /// Check the hole card for a dealer natural, ending the round if either side holds one.
+    /// Without a hole card a player natural against an ace or ten waits on the dealer's second
+    /// card instead.
+    fn peek(&mut self) {
+        self.peek_pending = false;
+        if self.rules.no_hole_card {
+            if !self.current_hand().is_natural() {
+                return;
+            }
+            if self.dealer_may_have_blackjack() {
+                self.deal(Seat::Dealer);
+            }
+        }
+        self.settle_insurance();
+        let outcome = match (
+            self.current_hand().is_natural(),
+            self.dealer_has_blackjack(),
+        ) {
+            (true, true) => Outcome::Push,
+            (true, false) => Outcome::Blackjack,
+            (false, true) => Outcome::Lose,
+            (false, false) => return,
+        };
+        self.flip_upcard();
+        self.settle_hand(0, outcome);
+        if outcome == Outcome::Blackjack {
+            self.set_state(GameState::Blackjack);
+        } else {
+            self.finish_round();
+        }
+    }
+
+    /// Split aces that may only receive one card
+    fn hand_locked(&self) -> bool {
+        self.current_hand().is_split_aces() && self.rules.split_aces_one_card
+    }
+
+    /// Play a command, or explain why it cannot be played without changing the table
+    pub fn run(&mut self, command: Command) -> Result<(), GameError> {
+        self.check(command)?;
+        // Any play other than early surrender lets the dealer peek first
+        let player_turn = self.state == GameState::PlayerTurn;
+        if self.peek_pending && player_turn && command != Command::Surrender {
+            self.peek();
+            if self.state != GameState::PlayerTurn {
+                return Ok(());
+            }
+        }
         match command {
             Command::Hit => {
-                self.player_hand.push(Card::new());
-                if self.player_score() > BLACKJACK {
-                    self.state = GameState::Lose;
-                    self.bank -= self.current_bet;
+                self.deal(Seat::Player(self.active_hand));
+                let value = self.player_value();
+                if value.is_bust {
+                    self.settle_hand(self.active_hand, Outcome::Lose);
+                    self.next_hand();
+                } else if value.total == BLACKJACK {
+                    self.next_hand();
                 }
-                if self.player_score() == BLACKJACK {
-                    self.state = GameState::DealerTurn;
-                    self.flip_upcard();
+            }
+            Command::Stand => self.next_hand(),
+            Command::Double => {
+                self.emit(Event::BetDoubled {
+                    hand: self.active_hand,
+                });
+                self.deal(Seat::Player(self.active_hand));
+                if self.player_value().is_bust {
+                    self.settle_hand(self.active_hand, Outcome::Lose);
                 }
+                self.next_hand();
             }
-            Command::Stand => {
-                self.state = GameState::DealerTurn;
+            Command::Surrender => {
+                self.peek_pending = false;
+                self.settle_hand(self.active_hand, Outcome::Surrender);
+                // Early surrender comes before the peek, so insurance is still open
+                self.settle_insurance();
+                self.set_state(GameState::Surrender);
                 self.flip_upcard();
-            },
+            }
+            Command::Insurance(stake) => {
+                if stake > 0 {
+                    self.emit(Event::InsurancePlaced { stake });
+                }
+                self.begin_play();
+            }
+            Command::EvenMoney => {
+                self.peek_pending = false;
+                self.flip_upcard();
+                self.settle_hand(0, Outcome::Win);
+                self.finish_round();
+            }
             Command::AdvanceDealer => {
-                if self.dealer_score() < DEALER_STAND {
-                    self.dealer_hand.push(Card::new());
-                } else if self.dealer_score() > BLACKJACK
-                    || self.dealer_score() < self.player_score()
-                {
-                    // Ensure dealer does not run after player has already lost
-                    assert!(self.player_score() <= BLACKJACK);
-                    self.state = GameState::Win;
-                    self.bank += self.current_bet;
-                } else if self.dealer_score() == self.player_score() {
-                    self.state = GameState::Draw;
-                } else {
-                    self.state = GameState::Lose;
-                    self.bank -= self.current_bet;
+                if self.dealer_must_hit() {
+                    self.deal(Seat::Dealer);
+                    return Ok(());
+                }
+                let dealer = self.dealer_value();
+                let dealer_natural = self.dealer_has_blackjack();
+                // Busted hands were settled as they were played
+                for i in 0..self.hands.len() {
+                    if self.hands[i].outcome.is_some() {
+                        continue;
+                    }
+                    let player = self.hands[i].value();
+                    let outcome = if dealer_natural {
+                        Outcome::Lose
+                    } else if dealer.is_bust || dealer.total < player.total {
+                        Outcome::Win
+                    } else if dealer.total == player.total {
+                        Outcome::Push
+                    } else {
+                        Outcome::Lose
+                    };
+                    self.settle_hand(i, outcome);
                 }
+                self.finish_round();
             }
-            Command::Split => todo!(),
+            Command::Split => {
+                self.emit(Event::HandSplit {
+                    hand: self.active_hand,
+                });
+                self.deal_split_card();
+            }
+        }
+        Ok(())
+    }
+
+    /// Deal the second card to a hand created by a split, moving on if it makes 21 or the hand
+    /// may not be played further
+    fn deal_split_card(&mut self) {
+        self.deal(Seat::Player(self.active_hand));
+        if self.player_score() == BLACKJACK || (self.hand_locked() && !self.can_split()) {
+            self.next_hand();
+        }
+    }
+
+    /// Move on to the next hand, or to the dealer once every hand has been played
+    fn next_hand(&mut self) {
+        if self.active_hand + 1 < self.hands.len() {
+            self.emit(Event::HandActivated {
+                hand: self.active_hand + 1,
+            });
+            self.deal_split_card();
+        } else if self.hands.iter().all(|hand| hand.outcome.is_some()) {
+            self.finish_round();
+        } else {
+            self.set_state(GameState::DealerTurn);
+            self.flip_upcard();
+        }
+    }
+
+    /// Amount won (positive) or lost (negative) by a hand with the given outcome
+    fn payout(&self, hand: &Hand, outcome: Outcome) -> i64 {
+        match outcome {
+            Outcome::Win => hand.bet as i64,
+            Outcome::Blackjack => self.blackjack_payout as i64,
+            Outcome::Lose => -(hand.bet as i64),
+            Outcome::Push => 0,
+            Outcome::Surrender => -((hand.bet / 2) as i64),
+        }
+    }
+
+    fn settle_hand(&mut self, index: usize, outcome: Outcome) {
+        let amount = self.payout(&self.hands[index], outcome);
+        self.emit(Event::HandSettled {
+            hand: index,
+            outcome,
+            amount,
+        });
+        self.change_bank(amount);
+    }
+
+    /// Resolve the insurance bet, dealing the dealer's second card first if there is none yet
+    fn settle_insurance(&mut self) {
+        if self.insurance == 0 || self.insurance_outcome.is_some() {
+            return;
+        }
+        if self.dealer_hand.len() < 2 {
+            self.deal(Seat::Dealer);
         }
+        let stake = self.insurance as i64;
+        let (outcome, amount) = if self.dealer_has_blackjack() {
+            (Outcome::Win, 2 * stake)
+        } else {
+            (Outcome::Lose, -stake)
+        };
+        self.emit(Event::InsuranceSettled { outcome, amount });
+        self.change_bank(amount);
+    }
+
+    /// Set the final game state from the net result of every hand. The hole card is shown even
+    /// when every hand busted and the dealer did not play.
+    fn finish_round(&mut self) {
+        self.flip_upcard();
+        self.settle_insurance();
+        let state = match self.net_result() {
+            net if net > 0 => GameState::Win,
+            net if net < 0 => GameState::Lose,
+            _ => GameState::Draw,
+        };
+        self.set_state(state);
     }
 
     fn flip_upcard(&mut self) {
-        self.dealer_hand[0].face_up();
+        if let Some(hole_card) = self.dealer_hand.get(1).filter(|card| card.down) {
+            let mut card = hole_card.clone();
+            card.face_up();
+            self.emit(Event::HoleCardRevealed { card });
+        }
     }
 }
 
-/// Default bank amount set to $100
+/// A single player hand and the amount wagered on it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hand {
+    pub cards: Vec<Card>,
+    pub bet: u32,
+    /// Set once the hand has been settled against the dealer
+    pub outcome: Option<Outcome>,
+    /// The hand was created by splitting a pair
+    pub split: bool,
+}
+
+impl Hand {
+    pub fn new(cards: Vec<Card>, bet: u32) -> Self {
+        Hand {
+            cards,
+            bet,
+            outcome: None,
+            split: false,
+        }
+    }
+
+    /// Value of the hand; a two-card 21 after a split is not a blackjack
+    pub fn value(&self) -> HandValue {
+        let mut value = HandValue::of(&self.cards);
+        value.is_blackjack &= !self.split;
+        value
+    }
+
+    /// Two cards of the same rank
+    pub fn is_pair(&self) -> bool {
+        self.value().is_pair
+    }
+
+    /// Two-card 21 on the original hand
+    pub fn is_natural(&self) -> bool {
+        self.value().is_blackjack
+    }
+
+    pub fn is_split_aces(&self) -> bool {
+        self.split && self.cards[0].rank == Rank::Ace
+    }
+}
+
+/// Default bank amount set to $100 at the default rules with a randomly seeded shoe
 impl Default for App {
     fn default() -> Self {
-        App::new(100)
+        App::new(DEFAULT_BANK, Rules::default(), fastrand::u64(..))
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GameState {
     EnterBet,
+    /// Dealer shows an ace; the player may insure or take even money
+    Insurance,
     PlayerTurn,
     DealerTurn,
     Win,
     Lose,
     Blackjack,
     Draw,
+    Surrender,
 }
 
-#[derive(Debug)]
+/// Stage of the round, as worded in error messages
+impl Display for GameState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameState::EnterBet => write!(f, "betting"),
+            GameState::Insurance => write!(f, "the insurance offer"),
+            GameState::PlayerTurn => write!(f, "your turn"),
+            GameState::DealerTurn => write!(f, "the dealer's turn"),
+            _ => write!(f, "the end of the round"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Win,
+    Lose,
+    Push,
+    Blackjack,
+    Surrender,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Command {
     Hit,
     Stand,
+    Double,
+    Surrender,
+    /// Stake the given amount on insurance; zero declines
+    Insurance(u32),
+    EvenMoney,
     AdvanceDealer,
     Split,
 }
 
-/// Calculate current score of blackjack hand. Aces are scored as 11 unless the total score is
-/// above 21, in which case they are scored as 1.
-fn calc_hand_score(hand: &[Card]) -> u8 {
-    let mut aces = 0;
-    let mut score = 0;
-    for card in hand {
-        if let Rank::Ace = card.rank {
-            aces += 1;
+impl Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Command::Hit => write!(f, "hit"),
+            Command::Stand => write!(f, "stand"),
+            Command::Double => write!(f, "double"),
+            Command::Surrender => write!(f, "surrender"),
+            Command::Insurance(0) => write!(f, "decline insurance"),
+            Command::Insurance(_) => write!(f, "insure"),
+            Command::EvenMoney => write!(f, "take even money"),
+            Command::AdvanceDealer => write!(f, "play the dealer's hand"),
+            Command::Split => write!(f, "split"),
         }
-        score += calc_card_score(card);
     }
+}
+
+/// Why `App` refused a bet or command. The table is left untouched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameError {
+    /// The command does not belong to the current stage of the round
+    IllegalAction {
+        command: Command,
+        state: GameState,
+    },
+    /// The table rules do not allow the command on the active hand
+    NotAllowed(Command),
+    InsufficientFunds {
+        needed: u32,
+        available: u32,
+    },
+    BetOutOfLimits {
+        bet: u32,
+        min: u32,
+        max: u32,
+    },
+    /// A new bet or deal was attempted before the last round was cleared, or the table was
+    /// cleared before the round was over
+    RoundInProgress,
+    /// A round was dealt before a bet was placed
+    NoBet,
+}
 
-    // Adjust Aces value downward if necessary
-    while score > BLACKJACK && aces > 0 {
-        score -= ACE_HIGH - ACE_LOW; // note operator precedence
-        aces -= 1;
-        assert!(score >= 2);
+impl Display for GameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameError::IllegalAction { command, state } => {
+                write!(f, "Cannot {} during {}", command, state)
+            }
+            GameError::NotAllowed(command) => {
+                write!(f, "Cannot {} this hand", command)
+            }
+            GameError::InsufficientFunds { needed, available } => write!(
+                f,
+                "Not enough money: ${} needed, ${} available",
+                needed, available
+            ),
+            GameError::BetOutOfLimits { bet, min, max } => {
+                write!(f, "${} is outside the limits of ${}-${}", bet, min, max)
+            }
+            GameError::RoundInProgress => write!(f, "Finish the current round first"),
+            GameError::NoBet => write!(f, "Place a bet first"),
+        }
     }
-    score
 }
 
-fn calc_card_score(card: &Card) -> u8 {
-    match card.rank {
-        Rank::Ace => ACE_HIGH,
-        Rank::Pip(num) => num,
-        _ => FACECARD,
+impl Error for GameError {}
+
+/// Why `App::replay` could not rebuild a table from an event stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The event at `position` in the stream refers to a hand that does not exist at that point
+    UnknownHand { position: usize, hand: usize },
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::UnknownHand { position, hand } => {
+                write!(f, "Event {} refers to missing hand {}", position, hand)
+            }
+        }
     }
 }
 
+impl Error for ReplayError {}
+
 #[cfg(test)]
 mod test {
     use super::*;
+
+    const SEED: u64 = 17;
+
+    fn seeded_app(seed: u64) -> App {
+        App::new(DEFAULT_BANK, Rules::default(), seed)
+    }
+
+    fn ranks(hand: &[Card]) -> Vec<Rank> {
+        hand.iter().map(|card| card.rank).collect()
+    }
+
+    /// Decline insurance, stand and let the dealer finish the round
+    fn play_out(app: &mut App) {
+        loop {
+            let command = match app.state {
+                GameState::Insurance => Command::Insurance(0),
+                GameState::PlayerTurn => Command::Stand,
+                GameState::DealerTurn => Command::AdvanceDealer,
+                _ => break,
+            };
+            app.run(command).unwrap();
+        }
+    }
+
     #[test]
     fn deal() {
-        let mut app = App::default();
-        app.start();
-        let player_count = app.player_hand.len();
+        let mut app = seeded_app(SEED);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        let player_count = app.player_hand().unwrap().cards.len();
         let dealer_count = app.dealer_hand.len();
         assert_eq!(2, player_count);
         assert_eq!(2, dealer_count);
@@ -183,10 +935,11 @@ mod test {
 
     #[test]
     fn hit() {
-        let mut app = App::default();
-        app.start();
-        app.run(Command::Hit);
-        let player_count = app.player_hand.len();
+        let mut app = seeded_app(SEED);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Hit).unwrap();
+        let player_count = app.player_hand().unwrap().cards.len();
         let dealer_count = app.dealer_hand.len();
         assert_eq!(3, player_count);
         assert_eq!(2, dealer_count);
@@ -194,12 +947,761 @@ mod test {
 
     #[test]
     fn stand() {
-        let mut app = App::default();
-        app.start();
+        let mut app = seeded_app(SEED);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
         let old_player_score = app.player_score();
-        app.run(Command::Stand);
+        app.run(Command::Stand).unwrap();
+        while let GameState::DealerTurn = app.state {
+            app.run(Command::AdvanceDealer).unwrap();
+        }
         assert_eq!(old_player_score, app.player_score());
-        matches!(app.state, GameState::Win | GameState::Lose);
+        assert!(matches!(
+            app.state,
+            GameState::Win | GameState::Lose | GameState::Draw
+        ));
+    }
+
+    #[test]
+    fn replay_from_seed() {
+        let mut first = seeded_app(SEED);
+        let mut second = seeded_app(SEED);
+        for _ in 0..20 {
+            first.place_bet(10).unwrap();
+            first.start().unwrap();
+            second.place_bet(10).unwrap();
+            second.start().unwrap();
+            assert_eq!(
+                ranks(&first.player_hand().unwrap().cards),
+                ranks(&second.player_hand().unwrap().cards)
+            );
+            assert_eq!(ranks(&first.dealer_hand), ranks(&second.dealer_hand));
+            for app in [&mut first, &mut second] {
+                play_out(app);
+                app.reset().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn deal_from_shoe() {
+        let mut app = App::new(
+            100,
+            Rules {
+                decks: 1,
+                ..Rules::default()
+            },
+            SEED,
+        );
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        assert_eq!(48, app.shoe.remaining());
+        app.run(Command::Hit).unwrap();
+        assert_eq!(47, app.shoe.remaining());
+    }
+
+    #[test]
+    fn reshuffle_between_rounds() {
+        let mut app = App::new(
+            100,
+            Rules {
+                decks: 1,
+                penetration: 50,
+                ..Rules::default()
+            },
+            SEED,
+        );
+        while !app.shoe.cut_card_reached() {
+            app.shoe.draw();
+        }
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        play_out(&mut app);
+        assert!(!app.shuffled);
+        app.reset().unwrap();
+        assert!(app.shuffled);
+        assert_eq!(52, app.shoe.remaining());
+        app.place_bet(10).unwrap();
+        assert!(!app.shuffled);
+        app.start().unwrap();
+        play_out(&mut app);
+        app.reset().unwrap();
+        assert!(!app.shuffled);
+    }
+
+    #[test]
+    fn split_pair() {
+        let mut app = seeded_app(SEED);
+        app.shoe.stack(&[
+            Rank::Pip(8),
+            Rank::Pip(7),
+            Rank::Pip(8),
+            Rank::King,
+            Rank::Pip(3),
+            Rank::Queen,
+        ]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        assert!(app.can_split());
+        app.run(Command::Split).unwrap();
+        assert_eq!(2, app.hands.len());
+        assert_eq!(11, app.player_score());
+        assert_eq!(1, app.hands[1].cards.len());
+
+        app.run(Command::Stand).unwrap();
+        assert_eq!(1, app.active_hand);
+        assert_eq!(18, app.player_score());
+
+        app.run(Command::Stand).unwrap();
+        while let GameState::DealerTurn = app.state {
+            app.run(Command::AdvanceDealer).unwrap();
+        }
+        assert_eq!(Some(Outcome::Lose), app.hands[0].outcome);
+        assert_eq!(Some(Outcome::Win), app.hands[1].outcome);
+        assert!(matches!(app.state, GameState::Draw));
+        assert_eq!(100, app.bank);
+    }
+
+    #[test]
+    fn split_requires_funds() {
+        let mut app = seeded_app(SEED);
+        app.shoe
+            .stack(&[Rank::Pip(8), Rank::Pip(7), Rank::Pip(8), Rank::King]);
+        app.place_bet(60).unwrap();
+        app.start().unwrap();
+        assert!(!app.can_split());
+        assert_eq!(
+            Err(GameError::InsufficientFunds {
+                needed: 60,
+                available: 40
+            }),
+            app.run(Command::Split)
+        );
+        assert_eq!(1, app.hands.len());
+    }
+
+    #[test]
+    fn resplit_up_to_max_hands() {
+        let mut app = seeded_app(SEED);
+        app.rules.max_hands = 3;
+        app.shoe.stack(&[
+            Rank::Pip(8),
+            Rank::Pip(7),
+            Rank::Pip(8),
+            Rank::King,
+            Rank::Pip(8),
+            Rank::Pip(8),
+        ]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Split).unwrap();
+        assert!(app.can_split());
+        app.run(Command::Split).unwrap();
+        assert_eq!(3, app.hands.len());
+        assert!(!app.can_split());
+    }
+
+    #[test]
+    fn split_aces_get_one_card() {
+        let stack = [
+            Rank::Ace,
+            Rank::Pip(7),
+            Rank::Ace,
+            Rank::King,
+            Rank::Pip(5),
+            Rank::Ace,
+        ];
+        let mut app = seeded_app(SEED);
+        app.shoe.stack(&stack);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Split).unwrap();
+        // Both hands are locked at two cards and the ace pair may not be resplit
+        assert!(matches!(app.state, GameState::DealerTurn));
+
+        let mut app = seeded_app(SEED);
+        app.rules.resplit_aces = true;
+        app.shoe.stack(&stack);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Split).unwrap();
+        // First hand is locked and play waits on the second hand, which may be resplit
+        assert_eq!(1, app.active_hand);
+        assert!(matches!(app.state, GameState::PlayerTurn));
+        assert!(app.can_split());
+        assert_eq!(
+            Err(GameError::NotAllowed(Command::Hit)),
+            app.run(Command::Hit)
+        );
+        assert_eq!(2, app.player_hand().unwrap().cards.len());
+    }
+
+    #[test]
+    fn split_unlike_tens() {
+        let mut app = seeded_app(SEED);
+        app.shoe
+            .stack(&[Rank::King, Rank::Pip(7), Rank::Jack, Rank::Pip(9)]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        assert!(app.can_split());
+        app.rules.split_unlike_tens = false;
+        assert!(!app.can_split());
+    }
+
+    #[test]
+    fn double_down() {
+        let mut app = seeded_app(SEED);
+        app.shoe.stack(&[
+            Rank::Pip(6),
+            Rank::Pip(7),
+            Rank::Pip(5),
+            Rank::King,
+            Rank::King,
+        ]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        assert!(app.can_double());
+        app.run(Command::Double).unwrap();
+        assert_eq!(20, app.player_hand().unwrap().bet);
+        assert_eq!(3, app.player_hand().unwrap().cards.len());
+        assert!(matches!(app.state, GameState::DealerTurn));
+        while let GameState::DealerTurn = app.state {
+            app.run(Command::AdvanceDealer).unwrap();
+        }
+        assert_eq!(120, app.bank);
+    }
+
+    #[test]
+    fn double_rules() {
+        let mut app = seeded_app(SEED);
+        app.shoe.stack(&[
+            Rank::Pip(8),
+            Rank::Pip(7),
+            Rank::Pip(8),
+            Rank::King,
+            Rank::Pip(3),
+        ]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.rules.double = DoubleRule::NineToEleven;
+        assert!(!app.can_double());
+        app.run(Command::Split).unwrap();
+        assert_eq!(11, app.player_score());
+        assert!(app.can_double());
+        app.rules.double_after_split = false;
+        assert!(!app.can_double());
+    }
+
+    #[test]
+    fn double_requires_funds() {
+        let mut app = seeded_app(SEED);
+        app.shoe
+            .stack(&[Rank::Pip(6), Rank::Pip(7), Rank::Pip(5), Rank::King]);
+        app.place_bet(60).unwrap();
+        app.start().unwrap();
+        assert!(!app.can_double());
+    }
+
+    #[test]
+    fn surrender() {
+        let mut app = seeded_app(SEED);
+        app.rules.surrender = SurrenderRule::Late;
+        app.shoe
+            .stack(&[Rank::King, Rank::King, Rank::Pip(6), Rank::Pip(7)]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        assert!(app.can_surrender());
+        app.run(Command::Surrender).unwrap();
+        assert!(matches!(app.state, GameState::Surrender));
+        assert_eq!(95, app.bank);
+        assert_eq!(-5, app.net_result());
+    }
+
+    #[test]
+    fn early_surrender_against_natural() {
+        let stack = [Rank::King, Rank::Ace, Rank::Pip(6), Rank::King];
+        let mut app = seeded_app(SEED);
+        app.rules.surrender = SurrenderRule::Late;
+        app.shoe.stack(&stack);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Insurance(0)).unwrap();
+        // Dealer peeks before late surrender is offered
+        assert!(matches!(app.state, GameState::Lose));
+        assert_eq!(90, app.bank);
+
+        let mut app = seeded_app(SEED);
+        app.rules.surrender = SurrenderRule::Early;
+        app.shoe.stack(&stack);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Insurance(0)).unwrap();
+        assert!(matches!(app.state, GameState::PlayerTurn));
+        app.run(Command::Surrender).unwrap();
+        assert!(matches!(app.state, GameState::Surrender));
+        assert_eq!(95, app.bank);
+    }
+
+    #[test]
+    fn early_surrender_after_insurance() {
+        // Dealer blackjack: half the bet is lost, insurance pays 2:1
+        let mut app = seeded_app(SEED);
+        app.rules.surrender = SurrenderRule::Early;
+        app.shoe
+            .stack(&[Rank::King, Rank::Ace, Rank::Pip(6), Rank::King]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Insurance(5)).unwrap();
+        app.run(Command::Surrender).unwrap();
+        assert!(matches!(app.state, GameState::Surrender));
+        assert_eq!(Some(Outcome::Win), app.insurance_outcome);
+        assert_eq!(105, app.bank);
+        assert_eq!(5, app.net_result());
+
+        // No dealer blackjack: both half the bet and the insurance are lost
+        let mut app = seeded_app(SEED);
+        app.rules.surrender = SurrenderRule::Early;
+        app.shoe
+            .stack(&[Rank::King, Rank::Ace, Rank::Pip(6), Rank::Pip(7)]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Insurance(5)).unwrap();
+        app.run(Command::Surrender).unwrap();
+        assert_eq!(Some(Outcome::Lose), app.insurance_outcome);
+        assert_eq!(90, app.bank);
+        assert_eq!(-10, app.net_result());
+    }
+
+    #[test]
+    fn early_surrender_declined() {
+        let mut app = seeded_app(SEED);
+        app.rules.surrender = SurrenderRule::Early;
+        app.shoe.stack(&[
+            Rank::King,
+            Rank::Ace,
+            Rank::Pip(6),
+            Rank::King,
+            Rank::Pip(2),
+        ]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Insurance(0)).unwrap();
+        app.run(Command::Hit).unwrap();
+        // Dealer peeks once the player acts, ending the round before the hit is dealt
+        assert!(matches!(app.state, GameState::Lose));
+        assert_eq!(2, app.player_hand().unwrap().cards.len());
+    }
+
+    #[test]
+    fn dealer_peek() {
+        let mut app = seeded_app(SEED);
+        app.shoe
+            .stack(&[Rank::Pip(9), Rank::King, Rank::Pip(9), Rank::Ace]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        assert!(matches!(app.state, GameState::Lose));
+        assert!(!app.hole_card_hidden());
+        assert_eq!(90, app.bank);
+
+        // Nothing to find under a low upcard
+        let mut app = seeded_app(SEED);
+        app.shoe
+            .stack(&[Rank::Pip(9), Rank::Pip(6), Rank::Pip(9), Rank::Pip(5)]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        assert!(matches!(app.state, GameState::PlayerTurn));
+        assert!(app.hole_card_hidden());
+    }
+
+    #[test]
+    fn natural_against_natural() {
+        let mut app = seeded_app(SEED);
+        app.shoe
+            .stack(&[Rank::Ace, Rank::Ace, Rank::King, Rank::Queen]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Insurance(0)).unwrap();
+        assert!(matches!(app.state, GameState::Draw));
+        assert_eq!(Some(Outcome::Push), app.hands[0].outcome);
+        assert_eq!(100, app.bank);
+
+        let mut app = seeded_app(SEED);
+        app.shoe
+            .stack(&[Rank::Ace, Rank::Ace, Rank::King, Rank::Pip(6)]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Insurance(0)).unwrap();
+        assert!(matches!(app.state, GameState::Blackjack));
+        assert_eq!(115, app.bank);
+    }
+
+    #[test]
+    fn no_hole_card() {
+        let mut app = seeded_app(SEED);
+        app.rules.no_hole_card = true;
+        app.shoe.stack(&[
+            Rank::Pip(8),
+            Rank::Pip(10),
+            Rank::Pip(2),
+            Rank::Pip(9),
+            Rank::Ace,
+        ]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        assert_eq!(1, app.dealer_hand.len());
+        assert!(app.hole_card_hidden());
+        app.run(Command::Double).unwrap();
+        while let GameState::DealerTurn = app.state {
+            app.run(Command::AdvanceDealer).unwrap();
+        }
+        // Dealer natural beats the doubled 19
+        assert_eq!(Some(Outcome::Lose), app.hands[0].outcome);
+        assert_eq!(80, app.bank);
+
+        let mut app = seeded_app(SEED);
+        app.rules.no_hole_card = true;
+        app.shoe
+            .stack(&[Rank::Ace, Rank::Pip(10), Rank::King, Rank::Ace]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        assert_eq!(2, app.dealer_hand.len());
+        assert!(matches!(app.state, GameState::Draw));
+    }
+
+    #[test]
+    fn insurance_pays_against_natural() {
+        let mut app = seeded_app(SEED);
+        app.shoe
+            .stack(&[Rank::Pip(9), Rank::Ace, Rank::Pip(9), Rank::King]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        assert!(matches!(app.state, GameState::Insurance));
+        assert_eq!(5, app.max_insurance());
+        assert_eq!(
+            Err(GameError::BetOutOfLimits {
+                bet: 6,
+                min: 0,
+                max: 5
+            }),
+            app.run(Command::Insurance(6))
+        );
+        assert!(matches!(app.state, GameState::Insurance));
+        app.run(Command::Insurance(5)).unwrap();
+        assert!(matches!(app.state, GameState::Draw));
+        assert_eq!(Some(Outcome::Win), app.insurance_outcome);
+        assert_eq!(100, app.bank);
+    }
+
+    #[test]
+    fn insurance_lost() {
+        let mut app = seeded_app(SEED);
+        app.shoe
+            .stack(&[Rank::Pip(9), Rank::Ace, Rank::King, Rank::Pip(7)]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Insurance(4)).unwrap();
+        assert!(matches!(app.state, GameState::PlayerTurn));
+        assert_eq!(Some(Outcome::Lose), app.insurance_outcome);
+        assert_eq!(96, app.bank);
+        app.run(Command::Stand).unwrap();
+        while let GameState::DealerTurn = app.state {
+            app.run(Command::AdvanceDealer).unwrap();
+        }
+        assert_eq!(106, app.bank);
+        assert_eq!(6, app.net_result());
+    }
+
+    #[test]
+    fn even_money() {
+        let mut app = seeded_app(SEED);
+        app.shoe
+            .stack(&[Rank::Ace, Rank::Ace, Rank::King, Rank::King]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        assert!(matches!(app.state, GameState::Insurance));
+        app.run(Command::EvenMoney).unwrap();
+        assert!(matches!(app.state, GameState::Win));
+        assert_eq!(110, app.bank);
+
+        // Declining even money against a dealer natural is a push
+        let mut app = seeded_app(SEED);
+        app.shoe
+            .stack(&[Rank::Ace, Rank::Ace, Rank::King, Rank::King]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Insurance(0)).unwrap();
+        assert!(matches!(app.state, GameState::Draw));
+        assert_eq!(100, app.bank);
+    }
+
+    #[test]
+    fn dealer_soft_17() {
+        let stack = [
+            Rank::Pip(10),
+            Rank::Ace,
+            Rank::Pip(8),
+            Rank::Pip(6),
+            Rank::Pip(2),
+        ];
+        let mut app = seeded_app(SEED);
+        app.shoe.stack(&stack);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Insurance(0)).unwrap();
+        app.run(Command::Stand).unwrap();
+        while let GameState::DealerTurn = app.state {
+            app.run(Command::AdvanceDealer).unwrap();
+        }
+        assert_eq!(17, app.dealer_score());
+        assert!(matches!(app.state, GameState::Win));
+
+        let mut app = seeded_app(SEED);
+        app.rules.dealer_hits_soft_17 = true;
+        app.shoe.stack(&stack);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Insurance(0)).unwrap();
+        app.run(Command::Stand).unwrap();
+        while let GameState::DealerTurn = app.state {
+            app.run(Command::AdvanceDealer).unwrap();
+        }
+        assert_eq!(19, app.dealer_score());
+        assert!(matches!(app.state, GameState::Lose));
+    }
+
+    #[test]
+    fn blackjack_payout() {
+        let mut app = App::new(DEFAULT_BANK, Rules::downtown(), SEED);
+        app.shoe
+            .stack(&[Rank::Ace, Rank::Pip(9), Rank::King, Rank::Pip(7)]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        assert!(matches!(app.state, GameState::Blackjack));
+        assert_eq!(112, app.bank);
+    }
+
+    #[test]
+    fn refused_commands() {
+        let mut app = App::new(DEFAULT_BANK, Rules::vegas_strip(), SEED);
+        assert_eq!(
+            Err(GameError::BetOutOfLimits {
+                bet: 5,
+                min: 10,
+                max: 5000
+            }),
+            app.place_bet(5)
+        );
+        assert_eq!(
+            Err(GameError::InsufficientFunds {
+                needed: 200,
+                available: 100
+            }),
+            app.place_bet(200)
+        );
+        assert_eq!(
+            Err(GameError::IllegalAction {
+                command: Command::Hit,
+                state: GameState::EnterBet
+            }),
+            app.run(Command::Hit)
+        );
+        assert_eq!(Err(GameError::NoBet), app.start());
+        assert!(app.player_hand().is_none());
+        assert_eq!(0, app.player_score());
+        assert!(app.events().is_empty());
+
+        app.shoe
+            .stack(&[Rank::Pip(9), Rank::Pip(7), Rank::Pip(9), Rank::Pip(8)]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        assert_eq!(Err(GameError::RoundInProgress), app.start());
+        assert_eq!(Err(GameError::RoundInProgress), app.reset());
+        assert_eq!(
+            Err(GameError::IllegalAction {
+                command: Command::AdvanceDealer,
+                state: GameState::PlayerTurn
+            }),
+            app.run(Command::AdvanceDealer)
+        );
+        app.rules.double = DoubleRule::TenToEleven;
+        assert_eq!(
+            Err(GameError::NotAllowed(Command::Double)),
+            app.run(Command::Double)
+        );
+        assert_eq!(2, app.player_hand().unwrap().cards.len());
+    }
+
+    #[test]
+    fn legal_actions() {
+        let mut app = App::new(DEFAULT_BANK, Rules::vegas_strip(), SEED);
+        assert!(app.legal_actions().is_empty());
+
+        app.shoe.stack(&[
+            Rank::Pip(8),
+            Rank::Pip(7),
+            Rank::Pip(8),
+            Rank::Pip(9),
+            Rank::Pip(2),
+        ]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        assert_eq!(
+            vec![
+                Command::Hit,
+                Command::Stand,
+                Command::Double,
+                Command::Split,
+                Command::Surrender
+            ],
+            app.legal_actions()
+        );
+        app.run(Command::Hit).unwrap();
+        assert_eq!(vec![Command::Hit, Command::Stand], app.legal_actions());
+
+        let mut app = App::new(15, Rules::vegas_strip(), SEED);
+        app.shoe
+            .stack(&[Rank::Ace, Rank::Ace, Rank::King, Rank::Pip(9)]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        assert_eq!(
+            vec![Command::Insurance(5), Command::EvenMoney],
+            app.legal_actions()
+        );
+    }
+
+    #[test]
+    fn round_events() {
+        let mut app = seeded_app(SEED);
+        app.shoe
+            .stack(&[Rank::Pip(9), Rank::Pip(7), Rank::King, Rank::Pip(10)]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Stand).unwrap();
+        while let GameState::DealerTurn = app.state {
+            app.run(Command::AdvanceDealer).unwrap();
+        }
+        let dealt: Vec<(Seat, Rank, bool)> = app
+            .round_events()
+            .iter()
+            .filter_map(|event| match event {
+                Event::CardDealt { to, card } => Some((*to, card.rank, card.down)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (Seat::Player(0), Rank::Pip(9), false),
+                (Seat::Dealer, Rank::Pip(7), false),
+                (Seat::Player(0), Rank::King, false),
+                (Seat::Dealer, Rank::Pip(10), true),
+            ],
+            dealt
+        );
+        let tail = &app.round_events()[app.round_events().len() - 4..];
+        assert!(matches!(tail[0], Event::HoleCardRevealed { .. }));
+        assert_eq!(
+            Event::HandSettled {
+                hand: 0,
+                outcome: Outcome::Win,
+                amount: 10
+            },
+            tail[1]
+        );
+        assert_eq!(
+            Event::BankChanged {
+                bank: 110,
+                change: 10
+            },
+            tail[2]
+        );
+        assert_eq!(
+            Event::StateChanged {
+                state: GameState::Win
+            },
+            tail[3]
+        );
+
+        app.reset().unwrap();
+        assert!(app.round_events().is_empty());
+    }
+
+    #[test]
+    fn replay_events() {
+        let mut app = seeded_app(SEED);
+        app.shoe.stack(&[
+            Rank::Pip(8),
+            Rank::Pip(7),
+            Rank::Pip(8),
+            Rank::King,
+            Rank::Pip(3),
+            Rank::Queen,
+        ]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Split).unwrap();
+        app.run(Command::Double).unwrap();
+        app.run(Command::Stand).unwrap();
+        while let GameState::DealerTurn = app.state {
+            app.run(Command::AdvanceDealer).unwrap();
+        }
+        app.reset().unwrap();
+        app.place_bet(20).unwrap();
+        app.start().unwrap();
+
+        let replayed = App::replay(DEFAULT_BANK, Rules::default(), SEED, app.events()).unwrap();
+        assert_eq!(app.bank, replayed.bank);
+        assert_eq!(app.hands, replayed.hands);
+        assert_eq!(app.dealer_hand, replayed.dealer_hand);
+        assert_eq!(app.state, replayed.state);
+        assert_eq!(app.current_bet, replayed.current_bet);
+
+        let truncated = &app.events()[5..];
+        assert!(matches!(
+            App::replay(DEFAULT_BANK, Rules::default(), SEED, truncated),
+            Err(ReplayError::UnknownHand { .. })
+        ));
+    }
+
+    #[test]
+    fn replay_mid_round() {
+        // A single deck dealt to the last card runs dry mid-round every few rounds
+        let rules = Rules {
+            surrender: SurrenderRule::Early,
+            decks: 1,
+            penetration: 100,
+            ..Rules::default()
+        };
+        let mut app = App::new(1000, rules.clone(), SEED);
+        for _ in 0..40 {
+            app.place_bet(10).unwrap();
+            app.start().unwrap();
+            let mut replayed = App::replay(1000, rules.clone(), SEED, app.events()).unwrap();
+            // Both tables play on the same way and must deal the same cards
+            for table in [&mut app, &mut replayed] {
+                if table.state == GameState::Insurance {
+                    table.run(Command::Insurance(0)).unwrap();
+                }
+                if table.state == GameState::PlayerTurn {
+                    table.run(Command::Hit).unwrap();
+                }
+                if table.state == GameState::PlayerTurn {
+                    table.run(Command::Stand).unwrap();
+                }
+                while table.state == GameState::DealerTurn {
+                    table.run(Command::AdvanceDealer).unwrap();
+                }
+            }
+            assert_eq!(app.events(), replayed.events());
+            assert_eq!(app.bank, replayed.bank);
+            assert_eq!(app.shoe.remaining(), replayed.shoe.remaining());
+            app.reset().unwrap();
+        }
+        let shuffles = app
+            .events()
+            .iter()
+            .filter(|e| **e == Event::Shuffle)
+            .count();
+        assert!(shuffles >= 3);
     }
 
     #[test]
@@ -215,7 +1717,7 @@ mod test {
             down: false,
         };
         let hand = vec![jack_of_spades, two_of_diamonds];
-        assert_eq!(calc_hand_score(&hand), 12);
+        assert_eq!(HandValue::of(&hand).total, 12);
 
         let ace_of_hearts = Card {
             suit: Suit::Hearts,
@@ -228,7 +1730,7 @@ mod test {
             down: false,
         };
         let hand = vec![ace_of_hearts, king_of_diamonds];
-        assert_eq!(calc_hand_score(&hand), 21);
+        assert_eq!(HandValue::of(&hand).total, 21);
 
         let ace_of_hearts = Card {
             suit: Suit::Hearts,
@@ -241,7 +1743,7 @@ mod test {
             down: false,
         };
         let hand = vec![ace_of_hearts, ace_of_spades];
-        assert_eq!(calc_hand_score(&hand), 12);
+        assert_eq!(HandValue::of(&hand).total, 12);
 
         let three_of_hearts = Card {
             suit: Suit::Hearts,
@@ -254,7 +1756,7 @@ mod test {
             down: false,
         };
         let hand = vec![three_of_hearts, four_of_clubs];
-        assert_eq!(calc_hand_score(&hand), 7);
+        assert_eq!(HandValue::of(&hand).total, 7);
 
         // Ensure scoring logic for aces is working appropriately
         let mut cards: Vec<Card> = Vec::new();
@@ -265,6 +1767,6 @@ mod test {
             down: false,
             })
         }
-        assert_eq!(calc_hand_score(&cards), 12);
+        assert_eq!(HandValue::of(&cards).total, 12);
     }
 }
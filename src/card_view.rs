@@ -0,0 +1,219 @@
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Clear, Paragraph},
+    Frame,
+};
+
+use tui_blackjack::cards::{Card, Rank};
+
+pub const CARD_WIDTH: u16 = 11;
+pub const CARD_HEIGHT: u16 = 9;
+/// Narrowest strip of an overlapped card that still shows its border and corner index
+const MIN_STEP: u16 = 4;
+
+// Pip columns inside the card border
+const LEFT: usize = 2;
+const MIDDLE: usize = 4;
+const RIGHT: usize = 6;
+
+/// Where each card of a hand goes when the hand is fanned out left to right inside `area`, later
+/// cards overlapping earlier ones. `None` when the cards do not fit and the compact one-line form
+/// should be used instead.
+pub fn fan(area: Rect, count: usize) -> Option<Vec<Rect>> {
+    if area.height < CARD_HEIGHT || area.width < CARD_WIDTH {
+        return None;
+    }
+    let count = count as u16;
+    let step = match count {
+        0 | 1 => 0,
+        _ => ((area.width - CARD_WIDTH) / (count - 1)).min(CARD_WIDTH + 1),
+    };
+    if count > 1 && step < MIN_STEP {
+        return None;
+    }
+    let total = CARD_WIDTH + step * count.saturating_sub(1);
+    let x = area.x + (area.width - total) / 2;
+    let slots = (0..count)
+        .map(|i| Rect {
+            x: x + i * step,
+            y: area.y,
+            width: CARD_WIDTH,
+            height: CARD_HEIGHT,
+        })
+        .collect();
+    Some(slots)
+}
+
+/// Draw a large card at `slot`. A `width` below 1 squeezes it towards its centre line, as when the
+/// card is being turned over.
+pub fn render_card(f: &mut Frame, slot: Rect, card: &Card, style: Style, width: f32) {
+    let full = CARD_WIDTH as usize;
+    let shown = ((full as f32 * width).ceil() as usize).clamp(1, full);
+    let skip = (full - shown) / 2;
+    let lines: Vec<Line> = card_art(card)
+        .iter()
+        .map(|row| Line::from(row.chars().skip(skip).take(shown).collect::<String>()))
+        .collect();
+    let area = Rect {
+        x: slot.x + skip as u16,
+        width: shown as u16,
+        ..slot
+    }
+    .intersection(f.size());
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).style(style), area);
+}
+
+/// Rows of a large card, border included: corner indices and suit pips on the face, or a
+/// patterned back when the card is face down
+pub fn card_art(card: &Card) -> Vec<String> {
+    let width = CARD_WIDTH as usize - 2;
+    let height = CARD_HEIGHT as usize - 2;
+    let mut grid = vec![vec![' '; width]; height];
+
+    if card.down {
+        for (y, row) in grid.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                *cell = if (x + y) % 2 == 0 { '▒' } else { '░' };
+            }
+        }
+    } else {
+        let suit = card.suit.symbol().chars().next().unwrap_or('?');
+        let label: Vec<char> = card.rank.label().chars().collect();
+        for (i, &c) in label.iter().enumerate() {
+            grid[0][i] = c;
+            grid[height - 1][width - label.len() + i] = c;
+        }
+        grid[1][0] = suit;
+        grid[height - 2][width - 1] = suit;
+
+        match card.rank {
+            Rank::Jack | Rank::Queen | Rank::King => {
+                let letter = label[0];
+                let frame = [
+                    ['╭', '─', '─', '─', '╮'],
+                    ['│', suit, letter, suit, '│'],
+                    ['╰', '─', '─', '─', '╯'],
+                ];
+                for (y, row) in frame.iter().enumerate() {
+                    grid[2 + y][LEFT..=RIGHT].copy_from_slice(row);
+                }
+            }
+            rank => {
+                for &(y, x) in pips(rank) {
+                    grid[y][x] = suit;
+                }
+            }
+        }
+    }
+
+    let border = "─".repeat(width);
+    let mut rows = vec![format!("┌{}┐", border)];
+    rows.extend(
+        grid.iter()
+            .map(|row| format!("│{}│", row.iter().collect::<String>())),
+    );
+    rows.push(format!("└{}┘", border));
+    rows
+}
+
+/// Positions of the suit pips on an ace or number card, as (row, column) inside the border
+fn pips(rank: Rank) -> &'static [(usize, usize)] {
+    match rank {
+        Rank::Pip(2) => &[(1, MIDDLE), (5, MIDDLE)],
+        Rank::Pip(3) => &[(1, MIDDLE), (3, MIDDLE), (5, MIDDLE)],
+        Rank::Pip(4) => &[(1, LEFT), (1, RIGHT), (5, LEFT), (5, RIGHT)],
+        Rank::Pip(5) => &[(1, LEFT), (1, RIGHT), (3, MIDDLE), (5, LEFT), (5, RIGHT)],
+        Rank::Pip(6) => &[
+            (1, LEFT),
+            (1, RIGHT),
+            (3, LEFT),
+            (3, RIGHT),
+            (5, LEFT),
+            (5, RIGHT),
+        ],
+        Rank::Pip(7) => &[
+            (1, LEFT),
+            (1, RIGHT),
+            (2, MIDDLE),
+            (3, LEFT),
+            (3, RIGHT),
+            (5, LEFT),
+            (5, RIGHT),
+        ],
+        Rank::Pip(8) => &[
+            (1, LEFT),
+            (1, RIGHT),
+            (2, MIDDLE),
+            (3, LEFT),
+            (3, RIGHT),
+            (4, MIDDLE),
+            (5, LEFT),
+            (5, RIGHT),
+        ],
+        Rank::Pip(9) => &[
+            (1, LEFT),
+            (1, RIGHT),
+            (2, LEFT),
+            (2, RIGHT),
+            (3, MIDDLE),
+            (4, LEFT),
+            (4, RIGHT),
+            (5, LEFT),
+            (5, RIGHT),
+        ],
+        Rank::Pip(10) => &[
+            (1, LEFT),
+            (1, RIGHT),
+            (2, LEFT),
+            (2, MIDDLE),
+            (2, RIGHT),
+            (4, LEFT),
+            (4, MIDDLE),
+            (4, RIGHT),
+            (5, LEFT),
+            (5, RIGHT),
+        ],
+        // A single large pip in the middle of an ace
+        _ => &[(3, MIDDLE)],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tui_blackjack::cards::Suit;
+
+    #[test]
+    fn card_art_fits_the_card() {
+        for rank in Rank::all() {
+            let card = Card::new(rank, Suit::Spades);
+            let art = card_art(&card);
+            assert_eq!(CARD_HEIGHT as usize, art.len());
+            assert!(art
+                .iter()
+                .all(|row| row.chars().count() == CARD_WIDTH as usize));
+            if let Rank::Pip(num) = rank {
+                let pips = art.concat().matches('\u{2660}').count();
+                // Two more suits sit under the corner indices
+                assert_eq!(num as usize + 2, pips);
+            }
+        }
+    }
+
+    #[test]
+    fn fan_overlaps_then_falls_back() {
+        let area = Rect::new(0, 0, 40, CARD_HEIGHT);
+        let slots = fan(area, 2).unwrap();
+        assert_eq!(CARD_WIDTH + 1, slots[1].x - slots[0].x);
+
+        let slots = fan(area, 6).unwrap();
+        assert!(slots[1].x - slots[0].x < CARD_WIDTH);
+        assert!(slots[5].right() <= area.right());
+
+        assert!(fan(area, 12).is_none());
+        assert!(fan(Rect::new(0, 0, 40, CARD_HEIGHT - 1), 1).is_none());
+    }
+}
@@ -5,7 +5,14 @@ const HEART_UNICODE: &str = "\u{2665}";
 const CLUB_UNICODE: &str = "\u{2663}";
 const DIAMOND_UNICODE: &str = "\u{2666}";
 
-#[derive(Debug)]
+pub const SUITS: [Suit; 4] = [Suit::Hearts, Suit::Diamonds, Suit::Spades, Suit::Clubs];
+pub const DECK_SIZE: usize = 52;
+pub const MIN_DECKS: u8 = 1;
+pub const MAX_DECKS: u8 = 8;
+pub const MIN_PENETRATION: u8 = 10;
+pub const MAX_PENETRATION: u8 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Suit {
     Hearts,
     Diamonds,
@@ -13,7 +20,18 @@ pub enum Suit {
     Clubs,
 }
 
-#[derive(Debug)]
+impl Suit {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Suit::Hearts => HEART_UNICODE,
+            Suit::Diamonds => DIAMOND_UNICODE,
+            Suit::Spades => SPADE_UNICODE,
+            Suit::Clubs => CLUB_UNICODE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Rank {
     Ace,
     Pip(u8),
@@ -22,42 +40,44 @@ pub enum Rank {
     King,
 }
 
-#[derive(Debug)]
-pub struct Card {
-    pub suit: Suit,
-    pub rank: Rank,
-    pub down: bool,
-}
-
-impl Default for Card {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl Card {
-    pub fn new() -> Self {
-        let value = fastrand::u8(1..=13);
-        assert!(value > 0 && value < 14);
-        let rank = match value {
+impl Rank {
+    /// All thirteen ranks in a suit, ace first
+    pub fn all() -> impl Iterator<Item = Rank> {
+        (1..=13).map(|value| match value {
             1 => Rank::Ace,
             11 => Rank::Jack,
             12 => Rank::Queen,
             13 => Rank::King,
             _ => Rank::Pip(value),
-        };
+        })
+    }
 
-        let suit = match fastrand::u8(0..=3) {
-            0 => Suit::Hearts,
-            1 => Suit::Diamonds,
-            2 => Suit::Spades,
-            3 => Suit::Clubs,
-            _ => panic!("Not a valid suit"),
-        };
-        
-        let down = false;
+    /// Index printed in the corner of a card: "A", "2" to "10", "J", "Q" or "K"
+    pub fn label(&self) -> String {
+        match self {
+            Rank::Ace => String::from("A"),
+            Rank::Pip(num) => num.to_string(),
+            Rank::Jack => String::from("J"),
+            Rank::Queen => String::from("Q"),
+            Rank::King => String::from("K"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Card {
+    pub suit: Suit,
+    pub rank: Rank,
+    pub down: bool,
+}
 
-        Card { suit, rank, down }
+impl Card {
+    pub fn new(rank: Rank, suit: Suit) -> Self {
+        Card {
+            suit,
+            rank,
+            down: false,
+        }
     }
 
     pub fn face_down(&mut self) {
@@ -86,13 +106,155 @@ impl Display for Card {
             Rank::King => String::from(" K "),
         };
 
-        let suit = match self.suit {
-            Suit::Hearts => HEART_UNICODE,
-            Suit::Diamonds => DIAMOND_UNICODE,
-            Suit::Spades => SPADE_UNICODE,
-            Suit::Clubs => CLUB_UNICODE,
+        let suit = self.suit.symbol();
+        write!(f, "| {}  {}  {} |", suit, rank, suit)
+    }
+}
+
+/// One or more 52-card decks shuffled together. Cards are dealt from the top (the end of the
+/// vector). A cut card is placed `penetration` percent of the way into the shoe; once it has been
+/// dealt the shoe should be reshuffled before the next round. Drawing from an empty shoe rebuilds
+/// and reshuffles it on the spot, although `App` reshuffles it itself so the shuffle is recorded.
+///
+/// Every shuffle draws from the shoe's own RNG, so two shoes built from the same seed deal exactly
+/// the same sequence of cards.
+#[derive(Debug)]
+pub struct Shoe {
+    decks: u8,
+    penetration: u8,
+    cards: Vec<Card>,
+    seed: u64,
+    rng: fastrand::Rng,
+}
+
+impl Shoe {
+    /// Deck count and penetration are clamped to the supported ranges
+    pub fn new(decks: u8, penetration: u8, seed: u64) -> Self {
+        let mut shoe = Shoe {
+            decks: decks.clamp(MIN_DECKS, MAX_DECKS),
+            penetration: penetration.clamp(MIN_PENETRATION, MAX_PENETRATION),
+            cards: Vec::with_capacity(decks as usize * DECK_SIZE),
+            seed,
+            rng: fastrand::Rng::with_seed(seed),
         };
+        shoe.shuffle();
+        shoe
+    }
 
-        write!(f, "| {}  {}  {} |", suit, rank, suit)
+    /// Gather every card back into the shoe and shuffle
+    pub fn shuffle(&mut self) {
+        self.cards.clear();
+        for _ in 0..self.decks {
+            for suit in SUITS {
+                for rank in Rank::all() {
+                    self.cards.push(Card::new(rank, suit));
+                }
+            }
+        }
+        self.rng.shuffle(&mut self.cards);
+    }
+
+    /// Place cards on top of the shoe so they are dealt next, in the order given
+    #[cfg(test)]
+    pub fn stack(&mut self, ranks: &[Rank]) {
+        for &rank in ranks.iter().rev() {
+            self.cards.push(Card::new(rank, Suit::Spades));
+        }
+    }
+
+    pub fn draw(&mut self) -> Card {
+        if self.cards.is_empty() {
+            self.shuffle();
+        }
+        self.cards.pop().expect("shoe is never empty after shuffle")
+    }
+
+    pub fn decks(&self) -> u8 {
+        self.decks
+    }
+
+    /// Seed the shoe was created with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn penetration(&self) -> u8 {
+        self.penetration
+    }
+
+    pub fn size(&self) -> usize {
+        self.decks as usize * DECK_SIZE
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn dealt(&self) -> usize {
+        self.size() - self.remaining()
+    }
+
+    /// Number of cards dealt before the cut card comes out
+    pub fn cut_card(&self) -> usize {
+        self.size() * self.penetration as usize / 100
+    }
+
+    pub fn cut_card_reached(&self) -> bool {
+        self.dealt() >= self.cut_card()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shoe_contains_full_decks() {
+        let mut shoe = Shoe::new(2, 75, 1);
+        assert_eq!(2 * DECK_SIZE, shoe.remaining());
+
+        let mut aces_of_spades = 0;
+        for _ in 0..2 * DECK_SIZE {
+            let card = shoe.draw();
+            if card.rank == Rank::Ace && card.suit == Suit::Spades {
+                aces_of_spades += 1;
+            }
+        }
+        assert_eq!(2, aces_of_spades);
+        assert_eq!(0, shoe.remaining());
+    }
+
+    #[test]
+    fn shoe_reshuffles_when_empty() {
+        let mut shoe = Shoe::new(1, 100, 1);
+        for _ in 0..DECK_SIZE {
+            shoe.draw();
+        }
+        shoe.draw();
+        assert_eq!(DECK_SIZE - 1, shoe.remaining());
+    }
+
+    #[test]
+    fn cut_card() {
+        let mut shoe = Shoe::new(1, 75, 1);
+        assert_eq!(39, shoe.cut_card());
+        for _ in 0..38 {
+            shoe.draw();
+        }
+        assert!(!shoe.cut_card_reached());
+        shoe.draw();
+        assert!(shoe.cut_card_reached());
+        shoe.shuffle();
+        assert!(!shoe.cut_card_reached());
+    }
+
+    #[test]
+    fn same_seed_same_cards() {
+        let mut first = Shoe::new(6, 75, 2024);
+        let mut second = Shoe::new(6, 75, 2024);
+        for _ in 0..2 * first.size() {
+            let (a, b) = (first.draw(), second.draw());
+            assert_eq!((a.rank, a.suit), (b.rank, b.suit));
+        }
     }
 }
@@ -0,0 +1,341 @@
+use std::{env, path::Path, time::Duration};
+
+use tui_blackjack::cards::{MAX_DECKS, MAX_PENETRATION, MIN_DECKS, MIN_PENETRATION};
+use tui_blackjack::count::{TagSystem, SYSTEMS};
+use tui_blackjack::deviation::IndexTable;
+use tui_blackjack::rules::{Rules, SurrenderRule, PRESETS};
+use tui_blackjack::table::Table;
+
+use crate::theme::{Theme, THEMES};
+
+pub const USAGE: &str = "Usage: tui-blackjack [--rules <preset> | --table <file>] [--decks <1-8>] \
+[--penetration <10-100>] [--seed <number>] [--surrender <none|late|early>] [--no-hole-card] \
+[--dealer-speed <slow|normal|fast|instant>] [--no-animations] [--theme <name>] \
+[--trainer] [--block-mistakes] [--count] [--count-system <name|file>] [--indices <file>]
+
+Rule presets: vegas-strip, atlantic-city, downtown, european
+Themes: classic, four-colour, high-contrast, colour-blind, monochrome
+Counting systems: hi-lo, ko, hi-opt-1, hi-opt-2, omega-2, zen, wong-halves, or a TOML or JSON file, see the systems/ directory for examples
+Index tables: TOML or JSON, see the indices/ directory for examples
+Table files: TOML or JSON, see the tables/ directory for examples";
+
+/// Options passed on the command line at startup
+#[derive(Debug, Default)]
+pub struct Args {
+    /// Table selected with `--rules` or `--table`; the other table flags are applied on top
+    pub table: Table,
+    pub decks: Option<u8>,
+    /// Percentage of the shoe dealt before the cut card comes out
+    pub penetration: Option<u8>,
+    /// Seed for the shoe's RNG; a random one is picked when not given
+    pub seed: Option<u64>,
+    pub surrender: Option<SurrenderRule>,
+    /// Play European no-hole-card rules
+    pub no_hole_card: bool,
+    pub dealer_speed: DealerSpeed,
+    /// Skip the dealing and chip animations
+    pub no_animations: bool,
+    pub theme: Theme,
+    /// Grade every play against basic strategy
+    pub trainer: bool,
+    /// Refuse wrong plays in trainer mode until the right one is made
+    pub block_mistakes: bool,
+    /// Counting practice: keep the running count and quiz the player on it
+    pub count: bool,
+    /// Counting system used in counting practice, Hi-Lo unless `--count-system` is given
+    pub count_system: TagSystem,
+    /// Index plays used on top of basic strategy once a count is kept with the system they were
+    /// made for, the Hi-Lo Illustrious 18 and Fab 4 unless `--indices` is given
+    pub indices: IndexTable,
+}
+
+/// How long the dealer pauses between cards
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DealerSpeed {
+    Slow,
+    #[default]
+    Normal,
+    Fast,
+    /// Play out the dealer's hand without pausing
+    Instant,
+}
+
+impl DealerSpeed {
+    pub fn delay(&self) -> Duration {
+        match self {
+            DealerSpeed::Slow => Duration::from_millis(1500),
+            DealerSpeed::Normal => Duration::from_millis(1000),
+            DealerSpeed::Fast => Duration::from_millis(400),
+            DealerSpeed::Instant => Duration::ZERO,
+        }
+    }
+}
+
+impl Args {
+    /// Table rules after applying any overrides to the chosen preset
+    pub fn rules(&self) -> Rules {
+        let mut rules = self.table.rules.clone();
+        if let Some(decks) = self.decks {
+            rules.decks = decks;
+        }
+        if let Some(penetration) = self.penetration {
+            rules.penetration = penetration;
+        }
+        if let Some(surrender) = self.surrender {
+            rules.surrender = surrender;
+        }
+        if self.no_hole_card {
+            rules.no_hole_card = true;
+        }
+        rules
+    }
+
+    pub fn parse() -> Result<Args, String> {
+        Args::parse_from(env::args().skip(1))
+    }
+
+    pub fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+        let mut parsed = Args::default();
+        let mut has_indices = false;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--rules" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.table.rules = Rules::preset(&value).ok_or_else(|| {
+                        format!(
+                            "Unknown rules preset {}, expected one of: {}",
+                            value,
+                            PRESETS.join(", ")
+                        )
+                    })?;
+                }
+                "--table" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.table = Table::load(&value).map_err(|err| err.to_string())?;
+                }
+                "--decks" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.decks = match value.parse::<u8>() {
+                        Ok(decks) if (MIN_DECKS..=MAX_DECKS).contains(&decks) => Some(decks),
+                        _ => {
+                            return Err(format!(
+                                "--decks must be a number from {} to {}",
+                                MIN_DECKS, MAX_DECKS
+                            ))
+                        }
+                    };
+                }
+                "--penetration" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.penetration = match value.trim_end_matches('%').parse::<u8>() {
+                        Ok(pen) if (MIN_PENETRATION..=MAX_PENETRATION).contains(&pen) => Some(pen),
+                        _ => {
+                            return Err(format!(
+                                "--penetration must be a percentage from {} to {}",
+                                MIN_PENETRATION, MAX_PENETRATION
+                            ))
+                        }
+                    };
+                }
+                "--seed" => {
+                    let value = next_value(&mut args, &arg)?;
+                    let seed = value
+                        .parse::<u64>()
+                        .map_err(|_| String::from("--seed must be a non-negative integer"))?;
+                    parsed.seed = Some(seed);
+                }
+                "--surrender" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.surrender = match value.as_str() {
+                        "none" => Some(SurrenderRule::None),
+                        "late" => Some(SurrenderRule::Late),
+                        "early" => Some(SurrenderRule::Early),
+                        _ => return Err(String::from("--surrender must be none, late or early")),
+                    };
+                }
+                "--no-hole-card" => parsed.no_hole_card = true,
+                "--no-animations" => parsed.no_animations = true,
+                "--trainer" => parsed.trainer = true,
+                "--count" => parsed.count = true,
+                "--block-mistakes" => {
+                    parsed.trainer = true;
+                    parsed.block_mistakes = true;
+                }
+                "--dealer-speed" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.dealer_speed = match value.as_str() {
+                        "slow" => DealerSpeed::Slow,
+                        "normal" => DealerSpeed::Normal,
+                        "fast" => DealerSpeed::Fast,
+                        "instant" => DealerSpeed::Instant,
+                        _ => {
+                            return Err(String::from(
+                                "--dealer-speed must be slow, normal, fast or instant",
+                            ))
+                        }
+                    };
+                }
+                "--count-system" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.count = true;
+                    parsed.count_system = match TagSystem::preset(&value) {
+                        Some(system) => system,
+                        None if Path::new(&value).extension().is_some() => {
+                            TagSystem::load(&value).map_err(|err| err.to_string())?
+                        }
+                        None => {
+                            return Err(format!(
+                                "Unknown counting system {}, expected one of: {} or a system file",
+                                value,
+                                SYSTEMS.join(", ")
+                            ))
+                        }
+                    };
+                }
+                "--indices" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.count = true;
+                    parsed.indices = IndexTable::load(&value).map_err(|err| err.to_string())?;
+                    has_indices = true;
+                }
+                "--theme" => {
+                    let value = next_value(&mut args, &arg)?;
+                    parsed.theme = Theme::by_name(&value).ok_or_else(|| {
+                        format!(
+                            "Unknown theme {}, expected one of: {}",
+                            value,
+                            THEMES.join(", ")
+                        )
+                    })?;
+                }
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+        }
+        // The bundled Hi-Lo S17 indices are simply left out with other systems and H17 tables
+        if has_indices && parsed.indices.system != parsed.count_system.name {
+            return Err(format!(
+                "Index table {} is for {}, not {}",
+                parsed.indices.name, parsed.indices.system, parsed.count_system.name
+            ));
+        }
+        let rules = parsed.rules();
+        if has_indices && parsed.indices.dealer_hits_soft_17 != rules.dealer_hits_soft_17 {
+            let soft_17 = if parsed.indices.dealer_hits_soft_17 {
+                "hits"
+            } else {
+                "stands on"
+            };
+            return Err(format!(
+                "Index table {} is for a dealer who {} soft 17",
+                parsed.indices.name, soft_17
+            ));
+        }
+        rules.validate()?;
+        Ok(parsed)
+    }
+}
+
+fn next_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("Missing value for {}", flag))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Args, String> {
+        Args::parse_from(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn table_options() {
+        assert_eq!(Some(1), parse("--decks 1").unwrap().decks);
+        assert_eq!(Some(8), parse("--decks 8").unwrap().decks);
+        for decks in ["0", "9", "six"] {
+            let err = parse(&format!("--decks {}", decks)).unwrap_err();
+            assert_eq!("--decks must be a number from 1 to 8", err);
+        }
+
+        assert_eq!(Some(10), parse("--penetration 10").unwrap().penetration);
+        assert_eq!(Some(100), parse("--penetration 100").unwrap().penetration);
+        assert_eq!(Some(75), parse("--penetration 75%").unwrap().penetration);
+        for penetration in ["9", "101", "most"] {
+            let err = parse(&format!("--penetration {}", penetration)).unwrap_err();
+            assert_eq!("--penetration must be a percentage from 10 to 100", err);
+        }
+
+        assert_eq!("Missing value for --decks", parse("--decks").unwrap_err());
+        assert_eq!("Unknown argument: --deck", parse("--deck 6").unwrap_err());
+    }
+
+    #[test]
+    fn display_options() {
+        let speeds = [
+            ("slow", DealerSpeed::Slow),
+            ("normal", DealerSpeed::Normal),
+            ("fast", DealerSpeed::Fast),
+            ("instant", DealerSpeed::Instant),
+        ];
+        for (name, speed) in speeds {
+            let args = parse(&format!("--dealer-speed {}", name)).unwrap();
+            assert_eq!(speed, args.dealer_speed);
+        }
+        assert_eq!(
+            "--dealer-speed must be slow, normal, fast or instant",
+            parse("--dealer-speed warp").unwrap_err()
+        );
+
+        for name in THEMES {
+            let args = parse(&format!("--theme {}", name)).unwrap();
+            assert_eq!(Theme::by_name(name), Some(args.theme));
+        }
+        assert_eq!(
+            format!("Unknown theme neon, expected one of: {}", THEMES.join(", ")),
+            parse("--theme neon").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn training_options() {
+        let args = parse("--trainer").unwrap();
+        assert!(args.trainer && !args.block_mistakes);
+        let args = parse("--block-mistakes").unwrap();
+        assert!(args.trainer && args.block_mistakes);
+        assert!(!parse("").unwrap().trainer);
+    }
+
+    #[test]
+    fn indices_match_counting_system() {
+        let indices =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("indices/illustrious-18-fab-4.toml");
+        let with_system = |system: &str| {
+            let args = [
+                "--count-system",
+                system,
+                "--indices",
+                indices.to_str().unwrap(),
+            ];
+            Args::parse_from(args.map(String::from))
+        };
+        let args = with_system("hi-lo").unwrap();
+        assert!(args.count);
+        assert_eq!("Hi-Lo", args.indices.system);
+        assert_eq!(
+            "Index table Illustrious 18 and Fab 4 is for Hi-Lo, not KO",
+            with_system("ko").unwrap_err()
+        );
+
+        let args = [
+            "--rules",
+            "downtown",
+            "--indices",
+            indices.to_str().unwrap(),
+        ];
+        assert_eq!(
+            "Index table Illustrious 18 and Fab 4 is for a dealer who stands on soft 17",
+            Args::parse_from(args.map(String::from)).unwrap_err()
+        );
+    }
+}
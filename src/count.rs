@@ -0,0 +1,533 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{Debug, Display},
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use crate::cards::{Rank, Shoe, DECK_SIZE, SUITS};
+use crate::event::Event;
+use crate::file::{load_file, FileError};
+
+/// Names accepted by `TagSystem::preset`
+pub const SYSTEMS: [&str; 7] = [
+    "hi-lo",
+    "ko",
+    "hi-opt-1",
+    "hi-opt-2",
+    "omega-2",
+    "zen",
+    "wong-halves",
+];
+
+/// A card counting system: points added to the running count for each card seen
+pub trait CountingSystem: Debug {
+    fn name(&self) -> &str;
+
+    fn tag(&self, rank: Rank) -> f32;
+
+    /// The tags of a full deck add up to zero, so the running count can be turned into a true
+    /// count. Unbalanced systems are played off the running count alone.
+    fn is_balanced(&self) -> bool;
+
+    /// Aces are tagged zero and tracked in a separate count
+    fn ace_side_count(&self) -> bool {
+        false
+    }
+
+    /// Running count straight after a shuffle
+    fn initial_count(&self, _decks: u8) -> f32 {
+        0.0
+    }
+}
+
+/// A counting system given by a table of tags, one per rank. The bundled systems are all tag
+/// systems and custom ones can be loaded from a TOML or JSON file:
+///
+/// ```toml
+/// name = "Hi-Lo Half Sevens"
+/// # Worked out from the tags when left out
+/// balanced = false
+/// ace_side_count = false
+/// # Running count after a shuffle: initial_count + initial_count_per_deck * decks
+/// initial_count = 0
+/// initial_count_per_deck = -2
+///
+/// # Face cards take the tag of the 10 unless given their own
+/// [tags]
+/// A = -1
+/// 2 = 1
+/// 3 = 1
+/// 4 = 1
+/// 5 = 1
+/// 6 = 1
+/// 7 = 0.5
+/// 8 = 0
+/// 9 = 0
+/// 10 = -1
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagSystem {
+    pub name: String,
+    /// Tags by rank, ace first as in `Rank::all`
+    pub tags: [f32; 13],
+    pub balanced: bool,
+    pub ace_side_count: bool,
+    pub initial_count: f32,
+    pub initial_count_per_deck: f32,
+}
+
+impl Default for TagSystem {
+    fn default() -> Self {
+        TagSystem::hi_lo()
+    }
+}
+
+impl TagSystem {
+    /// A balanced system with no side count, from the tags of ace to ten
+    fn balanced(name: &str, tags: [f32; 10]) -> TagSystem {
+        let mut all = [tags[9]; 13];
+        all[..10].copy_from_slice(&tags);
+        TagSystem {
+            name: String::from(name),
+            tags: all,
+            balanced: true,
+            ace_side_count: false,
+            initial_count: 0.0,
+            initial_count_per_deck: 0.0,
+        }
+    }
+
+    /// Look up one of the bundled `SYSTEMS`
+    pub fn preset(name: &str) -> Option<TagSystem> {
+        match name {
+            "hi-lo" => Some(TagSystem::hi_lo()),
+            "ko" => Some(TagSystem::ko()),
+            "hi-opt-1" => Some(TagSystem::hi_opt_1()),
+            "hi-opt-2" => Some(TagSystem::hi_opt_2()),
+            "omega-2" => Some(TagSystem::omega_2()),
+            "zen" => Some(TagSystem::zen()),
+            "wong-halves" => Some(TagSystem::wong_halves()),
+            _ => None,
+        }
+    }
+
+    /// +1 for two to six, 0 for seven to nine, -1 for tens and aces
+    pub fn hi_lo() -> TagSystem {
+        let tags = [-1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0, -1.0];
+        TagSystem::balanced("Hi-Lo", tags)
+    }
+
+    /// Knock-Out: Hi-Lo with the seven counted as well, so no true count is needed. The count
+    /// starts at 4 - 4 × decks.
+    pub fn ko() -> TagSystem {
+        let tags = [-1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, -1.0];
+        TagSystem {
+            balanced: false,
+            initial_count: 4.0,
+            initial_count_per_deck: -4.0,
+            ..TagSystem::balanced("KO", tags)
+        }
+    }
+
+    pub fn hi_opt_1() -> TagSystem {
+        let tags = [0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0, -1.0];
+        TagSystem {
+            ace_side_count: true,
+            ..TagSystem::balanced("Hi-Opt I", tags)
+        }
+    }
+
+    pub fn hi_opt_2() -> TagSystem {
+        let tags = [0.0, 1.0, 1.0, 2.0, 2.0, 1.0, 1.0, 0.0, 0.0, -2.0];
+        TagSystem {
+            ace_side_count: true,
+            ..TagSystem::balanced("Hi-Opt II", tags)
+        }
+    }
+
+    pub fn omega_2() -> TagSystem {
+        let tags = [0.0, 1.0, 1.0, 2.0, 2.0, 2.0, 1.0, 0.0, -1.0, -2.0];
+        TagSystem {
+            ace_side_count: true,
+            ..TagSystem::balanced("Omega II", tags)
+        }
+    }
+
+    pub fn zen() -> TagSystem {
+        let tags = [-1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 1.0, 0.0, 0.0, -2.0];
+        TagSystem::balanced("Zen", tags)
+    }
+
+    pub fn wong_halves() -> TagSystem {
+        let tags = [-1.0, 0.5, 1.0, 1.0, 1.5, 1.0, 0.5, 0.0, -0.5, -1.0];
+        TagSystem::balanced("Wong Halves", tags)
+    }
+
+    /// Read a counting system file
+    pub fn load(path: impl AsRef<Path>) -> Result<TagSystem, SystemError> {
+        let path = path.as_ref();
+        let file = load_file(path).map_err(SystemError)?;
+        TagSystem::from_file(file).map_err(|message| SystemError(FileError::invalid(path, message)))
+    }
+
+    fn from_file(file: SystemFile) -> Result<TagSystem, String> {
+        let mut tags = [0.0; 13];
+        let mut known = Vec::new();
+        for (tag, rank) in tags.iter_mut().zip(Rank::all()) {
+            let label = rank.label();
+            let face_card = matches!(rank, Rank::Jack | Rank::Queen | Rank::King);
+            *tag = match file.tags.get(&label) {
+                Some(&value) => value,
+                None if face_card => match file.tags.get("10") {
+                    Some(&value) => value,
+                    None => return Err(format!("no tag for {} or 10", label)),
+                },
+                None => return Err(format!("no tag for {}", label)),
+            };
+            known.push(label);
+        }
+        if let Some(unknown) = file.tags.keys().find(|label| !known.contains(label)) {
+            return Err(format!("unknown rank {} in tags", unknown));
+        }
+        let balanced = tags.iter().sum::<f32>().abs() < f32::EPSILON;
+        Ok(TagSystem {
+            name: file.name,
+            tags,
+            balanced: file.balanced.unwrap_or(balanced),
+            ace_side_count: file.ace_side_count,
+            initial_count: file.initial_count,
+            initial_count_per_deck: file.initial_count_per_deck,
+        })
+    }
+}
+
+impl CountingSystem for TagSystem {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn tag(&self, rank: Rank) -> f32 {
+        let index = match rank {
+            Rank::Ace => 0,
+            Rank::Pip(num) => num as usize - 1,
+            Rank::Jack => 10,
+            Rank::Queen => 11,
+            Rank::King => 12,
+        };
+        self.tags[index]
+    }
+
+    fn is_balanced(&self) -> bool {
+        self.balanced
+    }
+
+    fn ace_side_count(&self) -> bool {
+        self.ace_side_count
+    }
+
+    fn initial_count(&self, decks: u8) -> f32 {
+        self.initial_count + self.initial_count_per_deck * decks as f32
+    }
+}
+
+/// Layout of a counting system file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SystemFile {
+    name: String,
+    balanced: Option<bool>,
+    #[serde(default)]
+    ace_side_count: bool,
+    #[serde(default)]
+    initial_count: f32,
+    #[serde(default)]
+    initial_count_per_deck: f32,
+    /// Tags by rank label: "A", "2" to "10", "J", "Q" and "K"
+    tags: HashMap<String, f32>,
+}
+
+/// A counting system file could not be read, or is not a valid system
+#[derive(Debug)]
+pub struct SystemError(pub FileError);
+
+impl Display for SystemError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Counting system {}", self.0)
+    }
+}
+
+impl Error for SystemError {}
+
+/// Keeps the running count over every card the player has seen, read from `App::events`. The
+/// hole card counts once it is turned over; the count starts again after each shuffle.
+#[derive(Debug)]
+pub struct Counter {
+    system: Box<dyn CountingSystem>,
+    decks: u8,
+    pub running: f32,
+    /// Aces seen since the shuffle, for systems with an ace side count
+    pub aces: u32,
+    /// How far into `App::events` has been read
+    cursor: usize,
+    /// Quizzes answered, and answered right
+    pub quizzes: u32,
+    pub quizzes_right: u32,
+}
+
+impl Counter {
+    /// Count a shoe of `decks` decks with the given system
+    pub fn new(system: Box<dyn CountingSystem>, decks: u8) -> Counter {
+        Counter {
+            running: system.initial_count(decks),
+            system,
+            decks,
+            aces: 0,
+            cursor: 0,
+            quizzes: 0,
+            quizzes_right: 0,
+        }
+    }
+
+    pub fn system(&self) -> &dyn CountingSystem {
+        self.system.as_ref()
+    }
+
+    /// Count the cards revealed by events emitted since the last update
+    pub fn update(&mut self, events: &[Event]) {
+        for event in &events[self.cursor..] {
+            let card = match event {
+                Event::CardDealt { card, .. } if !card.down => card,
+                Event::HoleCardRevealed { card } => card,
+                Event::Shuffle => {
+                    self.running = self.system.initial_count(self.decks);
+                    self.aces = 0;
+                    continue;
+                }
+                _ => continue,
+            };
+            self.running += self.system.tag(card.rank);
+            if card.rank == Rank::Ace {
+                self.aces += 1;
+            }
+        }
+        self.cursor = events.len();
+    }
+
+    /// Running count per deck left in the shoe. `None` for unbalanced systems.
+    pub fn true_count(&self, shoe: &Shoe) -> Option<f32> {
+        self.system
+            .is_balanced()
+            .then(|| self.running / decks_left(shoe) as f32)
+    }
+
+    /// Aces left in the shoe and how many more that is than an average shoe would hold, for
+    /// systems with an ace side count
+    pub fn aces_left(&self, shoe: &Shoe) -> Option<(u32, f32)> {
+        if !self.system.ace_side_count() {
+            return None;
+        }
+        let left = (SUITS.len() as u32 * self.decks as u32).saturating_sub(self.aces);
+        let expected = SUITS.len() as f64 * decks_left(shoe);
+        Some((left, (left as f64 - expected) as f32))
+    }
+
+    /// Score a guess at the running count
+    pub fn answer(&mut self, guess: f32) -> bool {
+        let right = guess == self.running;
+        self.quizzes += 1;
+        if right {
+            self.quizzes_right += 1;
+        }
+        right
+    }
+}
+
+/// Decks still to be dealt, never less than one card's worth
+pub fn decks_left(shoe: &Shoe) -> f64 {
+    shoe.remaining().max(1) as f64 / DECK_SIZE as f64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::app::{App, Command, GameState};
+    use crate::cards::{Card, Suit};
+    use crate::event::Seat;
+    use crate::rules::Rules;
+
+    #[test]
+    fn counts_revealed_cards() {
+        let mut app = App::new(100, Rules::default(), 17);
+        app.shoe.stack(&[
+            Rank::Pip(2),
+            Rank::King,
+            Rank::Pip(5),
+            Rank::Pip(4),
+            Rank::Pip(3),
+            Rank::Queen,
+        ]);
+        let mut counter = Counter::new(Box::new(TagSystem::hi_lo()), app.rules.decks);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        counter.update(app.events());
+        // The face-down four is not counted yet
+        assert_eq!(1.0, counter.running);
+
+        app.run(Command::Hit).unwrap();
+        counter.update(app.events());
+        assert_eq!(2.0, counter.running);
+        app.run(Command::Stand).unwrap();
+        counter.update(app.events());
+        assert_eq!(3.0, counter.running);
+        while let GameState::DealerTurn = app.state {
+            app.run(Command::AdvanceDealer).unwrap();
+        }
+        counter.update(app.events());
+        assert_eq!(2.0, counter.running);
+        let true_count = 2.0 / decks_left(&app.shoe) as f32;
+        assert_eq!(Some(true_count), counter.true_count(&app.shoe));
+
+        let mut counter = Counter::new(Box::new(TagSystem::hi_lo()), 6);
+        counter.update(&[Event::Shuffle]);
+        assert_eq!(0.0, counter.running);
+        assert!(!counter.answer(1.0));
+        assert!(counter.answer(0.0));
+        assert_eq!((2, 1), (counter.quizzes, counter.quizzes_right));
+    }
+
+    #[test]
+    fn counts_hole_card_when_every_hand_busts() {
+        let mut app = App::new(100, Rules::default(), 17);
+        app.shoe.stack(&[
+            Rank::King,
+            Rank::Pip(5),
+            Rank::Pip(6),
+            Rank::Pip(4),
+            Rank::Queen,
+        ]);
+        let mut counter = Counter::new(Box::new(TagSystem::hi_lo()), app.rules.decks);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        app.run(Command::Hit).unwrap();
+        assert_eq!(GameState::Lose, app.state);
+        counter.update(app.events());
+        // King, five, six and queen net zero; the four turned over makes it +1
+        assert_eq!(1.0, counter.running);
+    }
+
+    #[test]
+    fn count_resets_when_shoe_runs_dry() {
+        let rules = Rules {
+            decks: 1,
+            penetration: 100,
+            ..Rules::default()
+        };
+        let mut app = App::new(1000, rules, 5);
+        let mut counter = Counter::new(Box::new(TagSystem::hi_lo()), app.rules.decks);
+        // With the cut card at the very end, one of the first rounds runs the shoe dry
+        let mut dry = false;
+        for _ in 0..20 {
+            app.reset().unwrap();
+            app.place_bet(10).unwrap();
+            app.start().unwrap();
+            loop {
+                let command = match app.state {
+                    GameState::Insurance => Command::Insurance(0),
+                    GameState::PlayerTurn => Command::Stand,
+                    GameState::DealerTurn => Command::AdvanceDealer,
+                    _ => break,
+                };
+                app.run(command).unwrap();
+            }
+            dry = app
+                .round_events()
+                .iter()
+                .skip_while(|event| !matches!(event, Event::BetPlaced { .. }))
+                .any(|event| *event == Event::Shuffle);
+            if dry {
+                break;
+            }
+        }
+        assert!(dry);
+        counter.update(app.events());
+        // Only the cards shown since the reshuffle count
+        let shuffle = app
+            .events()
+            .iter()
+            .rposition(|e| *e == Event::Shuffle)
+            .unwrap();
+        let shown: f32 = app.events()[shuffle..]
+            .iter()
+            .filter_map(|event| match event {
+                Event::CardDealt { card, .. } if !card.down => Some(card),
+                Event::HoleCardRevealed { card } => Some(card),
+                _ => None,
+            })
+            .map(|card| counter.system().tag(card.rank))
+            .sum();
+        assert_eq!(shown, counter.running);
+        assert!(app.shoe.remaining() > 40);
+    }
+
+    #[test]
+    fn bundled_systems() {
+        for name in SYSTEMS {
+            let system = TagSystem::preset(name).unwrap();
+            let deck: f32 = Rank::all().map(|rank| system.tag(rank)).sum();
+            assert_eq!(system.is_balanced(), deck == 0.0, "{}", name);
+            if system.ace_side_count() {
+                assert_eq!(0.0, system.tag(Rank::Ace), "{}", name);
+            }
+        }
+        assert_eq!(0.5, TagSystem::wong_halves().tag(Rank::Pip(2)));
+        assert_eq!(-2.0, TagSystem::zen().tag(Rank::King));
+
+        // KO starts low enough that a full shoe nets out at +4
+        let ko = Counter::new(Box::new(TagSystem::ko()), 6);
+        assert_eq!(-20.0, ko.running);
+        assert!(ko.true_count(&Shoe::new(6, 75, 1)).is_none());
+
+        let mut omega = Counter::new(Box::new(TagSystem::omega_2()), 1);
+        omega.update(&[Event::CardDealt {
+            to: Seat::Dealer,
+            card: Card::new(Rank::Ace, Suit::Clubs),
+        }]);
+        assert_eq!(0.0, omega.running);
+        assert_eq!(Some((3, -1.0)), omega.aces_left(&Shoe::new(1, 75, 1)));
+    }
+
+    #[test]
+    fn custom_systems() {
+        let systems = Path::new(env!("CARGO_MANIFEST_DIR")).join("systems");
+        for entry in std::fs::read_dir(systems).unwrap() {
+            let path = entry.unwrap().path();
+            let system = match TagSystem::load(&path) {
+                Ok(system) => system,
+                Err(err) => panic!("{}", err),
+            };
+            // Bundled files say whether they are balanced, and the tags agree
+            let file: SystemFile = load_file(&path).unwrap();
+            let deck: f32 = Rank::all().map(|rank| system.tag(rank)).sum();
+            assert_eq!(Some(deck == 0.0), file.balanced, "{}", system.name);
+        }
+        assert_eq!(
+            TagSystem::hi_opt_1().tags,
+            TagSystem::load(Path::new(env!("CARGO_MANIFEST_DIR")).join("systems/hi-opt-1.json"))
+                .unwrap()
+                .tags
+        );
+
+        let file = "name = \"Tens\"\n[tags]\nA = 0\n2 = 0\n3 = 0\n4 = 1\n5 = 1\n6 = 1\n7 = 1\n\
+                    8 = 0\n9 = 0\n10 = -1";
+        let system = TagSystem::from_file(toml::from_str(file).unwrap()).unwrap();
+        assert_eq!(-1.0, system.tag(Rank::Queen));
+        assert!(system.is_balanced());
+
+        let missing = "name = \"Short\"\n[tags]\nA = -1\n10 = -1";
+        let err = TagSystem::from_file(toml::from_str(missing).unwrap()).unwrap_err();
+        assert_eq!("no tag for 2", err);
+    }
+}
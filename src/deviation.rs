@@ -0,0 +1,358 @@
+use std::{error::Error, fmt::Display, path::Path};
+
+use serde::Deserialize;
+
+use crate::app::{App, Command, Hand};
+use crate::count::Counter;
+use crate::file::{load_file, FileError};
+use crate::hand::{ACE_HIGH, BLACKJACK, FACECARD};
+use crate::strategy::Row;
+
+/// A count-based change to basic strategy: against `upcard`, play `play` on `hand` once the true
+/// count reaches `index` and `otherwise` below it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Deviation {
+    /// Hard rows also cover pairs with the same total, e.g. 5,5 as a hard 10
+    pub hand: Row,
+    /// Dealer's upcard, as returned by `App::dealer_showing`
+    pub upcard: u8,
+    pub index: f32,
+    pub play: Command,
+    pub otherwise: Command,
+}
+
+impl Deviation {
+    /// Whether the entry is about this hand against this upcard
+    pub fn covers(&self, hand: &Hand, upcard: u8) -> bool {
+        let value = hand.value();
+        let hard_total = !value.is_soft && self.hand == Row::Hard(value.total);
+        upcard == self.upcard && (Row::of(hand) == self.hand || hard_total)
+    }
+
+    /// The play called for at this true count
+    pub fn play_at(&self, true_count: f32) -> Command {
+        if true_count >= self.index {
+            self.play
+        } else {
+            self.otherwise
+        }
+    }
+}
+
+const fn index(hand: Row, upcard: u8, index: f32, play: Command, otherwise: Command) -> Deviation {
+    Deviation {
+        hand,
+        upcard,
+        index,
+        play,
+        otherwise,
+    }
+}
+
+/// The Illustrious 18 bar insurance, with Hi-Lo indices for a multi-deck shoe
+const ILLUSTRIOUS_18: [Deviation; 17] = [
+    index(Row::Hard(16), FACECARD, 0.0, Command::Stand, Command::Hit),
+    index(Row::Hard(15), FACECARD, 4.0, Command::Stand, Command::Hit),
+    index(Row::Pair(FACECARD), 5, 5.0, Command::Split, Command::Stand),
+    index(Row::Pair(FACECARD), 6, 4.0, Command::Split, Command::Stand),
+    index(Row::Hard(10), FACECARD, 4.0, Command::Double, Command::Hit),
+    index(Row::Hard(12), 3, 2.0, Command::Stand, Command::Hit),
+    index(Row::Hard(12), 2, 3.0, Command::Stand, Command::Hit),
+    index(Row::Hard(11), ACE_HIGH, 1.0, Command::Double, Command::Hit),
+    index(Row::Hard(9), 2, 1.0, Command::Double, Command::Hit),
+    index(Row::Hard(10), ACE_HIGH, 4.0, Command::Double, Command::Hit),
+    index(Row::Hard(9), 7, 3.0, Command::Double, Command::Hit),
+    index(Row::Hard(16), 9, 5.0, Command::Stand, Command::Hit),
+    index(Row::Hard(13), 2, -1.0, Command::Stand, Command::Hit),
+    index(Row::Hard(12), 4, 0.0, Command::Stand, Command::Hit),
+    index(Row::Hard(12), 5, -2.0, Command::Stand, Command::Hit),
+    index(Row::Hard(12), 6, -1.0, Command::Stand, Command::Hit),
+    index(Row::Hard(13), 3, -2.0, Command::Stand, Command::Hit),
+];
+
+/// Late surrender indices to go with the Illustrious 18
+const FAB_4: [Deviation; 4] = [
+    index(
+        Row::Hard(14),
+        FACECARD,
+        3.0,
+        Command::Surrender,
+        Command::Hit,
+    ),
+    index(
+        Row::Hard(15),
+        FACECARD,
+        0.0,
+        Command::Surrender,
+        Command::Hit,
+    ),
+    index(Row::Hard(15), 9, 2.0, Command::Surrender, Command::Hit),
+    index(
+        Row::Hard(15),
+        ACE_HIGH,
+        1.0,
+        Command::Surrender,
+        Command::Hit,
+    ),
+];
+
+/// Index plays layered over basic strategy. Custom tables can be loaded from a TOML or JSON
+/// file:
+///
+/// ```toml
+/// name = "Illustrious 18"
+/// # Counting system the indices were worked out for, as named by `CountingSystem::name`
+/// system = "Hi-Lo"
+/// # Take insurance at this true count or above
+/// insurance = 3
+/// # Dealer rule the indices hold for; standing on soft 17 when left out
+/// dealer_hits_soft_17 = false
+///
+/// # Hands are a hard total ("16"), a soft total ("soft 18") or a pair ("8,8" or "A,A") and
+/// # upcards "2" to "10" or "A". Plays are hit, stand, double, split or surrender.
+/// [[deviations]]
+/// hand = "16"
+/// upcard = "10"
+/// index = 0
+/// play = "stand"
+/// otherwise = "hit"
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexTable {
+    pub name: String,
+    /// The indices only hold for true counts kept with this counting system
+    pub system: String,
+    /// True count at or above which insurance (or even money) is taken
+    pub insurance: Option<f32>,
+    /// The indices only hold at tables where the dealer hits (or stands on) soft 17 like this
+    pub dealer_hits_soft_17: bool,
+    /// Checked in order; the first entry covering the hand decides
+    pub deviations: Vec<Deviation>,
+}
+
+impl Default for IndexTable {
+    fn default() -> Self {
+        IndexTable::illustrious_18_fab_4()
+    }
+}
+
+impl IndexTable {
+    pub fn illustrious_18() -> IndexTable {
+        IndexTable {
+            name: String::from("Illustrious 18"),
+            system: String::from("Hi-Lo"),
+            insurance: Some(3.0),
+            dealer_hits_soft_17: false,
+            deviations: ILLUSTRIOUS_18.to_vec(),
+        }
+    }
+
+    pub fn fab_4() -> IndexTable {
+        IndexTable {
+            name: String::from("Fab 4"),
+            system: String::from("Hi-Lo"),
+            insurance: None,
+            dealer_hits_soft_17: false,
+            deviations: FAB_4.to_vec(),
+        }
+    }
+
+    /// The Illustrious 18 with the Fab 4 surrender indices checked first
+    pub fn illustrious_18_fab_4() -> IndexTable {
+        IndexTable {
+            name: String::from("Illustrious 18 and Fab 4"),
+            system: String::from("Hi-Lo"),
+            insurance: Some(3.0),
+            dealer_hits_soft_17: false,
+            deviations: FAB_4.iter().chain(&ILLUSTRIOUS_18).copied().collect(),
+        }
+    }
+
+    /// True count to play these indices at, or `None` when the count is kept with a different
+    /// system, or the dealer plays soft 17 differently, than the indices were worked out for
+    pub fn true_count(&self, counter: &Counter, app: &App) -> Option<f32> {
+        if counter.system().name() != self.system
+            || app.rules.dealer_hits_soft_17 != self.dealer_hits_soft_17
+        {
+            return None;
+        }
+        counter.true_count(&app.shoe)
+    }
+
+    /// Read an index table file
+    pub fn load(path: impl AsRef<Path>) -> Result<IndexTable, IndexError> {
+        let path = path.as_ref();
+        let file = load_file(path).map_err(IndexError)?;
+        IndexTable::from_file(file).map_err(|message| IndexError(FileError::invalid(path, message)))
+    }
+
+    fn from_file(file: IndexFile) -> Result<IndexTable, String> {
+        let deviations = file
+            .deviations
+            .iter()
+            .map(|entry| {
+                Ok(Deviation {
+                    hand: parse_hand(&entry.hand)?,
+                    upcard: parse_upcard(&entry.upcard)?,
+                    index: entry.index,
+                    play: parse_play(&entry.play)?,
+                    otherwise: parse_play(&entry.otherwise)?,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(IndexTable {
+            name: file.name,
+            system: file.system,
+            insurance: file.insurance,
+            dealer_hits_soft_17: file.dealer_hits_soft_17,
+            deviations,
+        })
+    }
+}
+
+fn parse_hand(text: &str) -> Result<Row, String> {
+    let text = text.trim();
+    let row = if let Some(total) = text.strip_prefix("soft ") {
+        total
+            .parse()
+            .ok()
+            .filter(|total| (13..=BLACKJACK).contains(total))
+            .map(Row::Soft)
+    } else if let Some((first, second)) = text.split_once(',') {
+        parse_upcard(first)
+            .ok()
+            .filter(|_| first.trim() == second.trim())
+            .map(Row::Pair)
+    } else {
+        text.parse()
+            .ok()
+            .filter(|total| (5..=BLACKJACK).contains(total))
+            .map(Row::Hard)
+    };
+    row.ok_or_else(|| format!("unknown hand {}", text))
+}
+
+fn parse_upcard(text: &str) -> Result<u8, String> {
+    match text.trim() {
+        "A" => Ok(ACE_HIGH),
+        value => value
+            .parse()
+            .ok()
+            .filter(|value| (2..=FACECARD).contains(value))
+            .ok_or_else(|| format!("unknown card {}", text)),
+    }
+}
+
+fn parse_play(text: &str) -> Result<Command, String> {
+    match text.trim() {
+        "hit" => Ok(Command::Hit),
+        "stand" => Ok(Command::Stand),
+        "double" => Ok(Command::Double),
+        "split" => Ok(Command::Split),
+        "surrender" => Ok(Command::Surrender),
+        _ => Err(format!("unknown play {}", text)),
+    }
+}
+
+/// Layout of an index table file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IndexFile {
+    name: String,
+    system: String,
+    insurance: Option<f32>,
+    #[serde(default)]
+    dealer_hits_soft_17: bool,
+    #[serde(default)]
+    deviations: Vec<DeviationFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DeviationFile {
+    hand: String,
+    upcard: String,
+    index: f32,
+    play: String,
+    otherwise: String,
+}
+
+/// An index table file could not be read, or names an unknown hand, card or play
+#[derive(Debug)]
+pub struct IndexError(pub FileError);
+
+impl Display for IndexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Index table {}", self.0)
+    }
+}
+
+impl Error for IndexError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cards::{Card, Rank, Suit};
+    use crate::count::TagSystem;
+    use crate::rules::Rules;
+
+    #[test]
+    fn index_table_files() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("indices/illustrious-18-fab-4.toml");
+        let table = IndexTable::load(path).unwrap();
+        assert_eq!(IndexTable::default(), table);
+
+        let file = "name = \"Tens\"\nsystem = \"Hi-Lo\"\n[[deviations]]\nhand = \"5,5\"\nupcard = \"A\"\nindex = 4\n\
+                    play = \"double\"\notherwise = \"hit\"";
+        let table = IndexTable::from_file(toml::from_str(file).unwrap()).unwrap();
+        let fives = Hand::new(
+            vec![
+                Card::new(Rank::Pip(5), Suit::Clubs),
+                Card::new(Rank::Pip(5), Suit::Hearts),
+            ],
+            10,
+        );
+        let deviation = table.deviations[0];
+        assert!(deviation.covers(&fives, ACE_HIGH));
+        assert!(!deviation.covers(&fives, FACECARD));
+        assert_eq!(Command::Hit, deviation.play_at(3.9));
+        assert_eq!(Command::Double, deviation.play_at(4.0));
+        // A hard 10 entry covers the pair as well
+        assert!(IndexTable::default()
+            .deviations
+            .iter()
+            .any(|d| d.covers(&fives, ACE_HIGH)));
+
+        let bad = file.replace("double", "insure");
+        let err = IndexTable::from_file(toml::from_str(&bad).unwrap()).unwrap_err();
+        assert_eq!("unknown play insure", err);
+    }
+
+    #[test]
+    fn indices_need_their_counting_system() {
+        let app = App::new(100, Rules::default(), 1);
+        let hi_lo = Counter::new(Box::new(TagSystem::hi_lo()), 6);
+        let zen = Counter::new(Box::new(TagSystem::zen()), 6);
+        let table = IndexTable::default();
+        assert_eq!(Some(0.0), table.true_count(&hi_lo, &app));
+        assert_eq!(None, table.true_count(&zen, &app));
+        let zen_table = IndexTable {
+            system: String::from("Zen"),
+            ..IndexTable::default()
+        };
+        assert_eq!(Some(0.0), zen_table.true_count(&zen, &app));
+    }
+
+    #[test]
+    fn indices_need_their_soft_17_rule() {
+        let h17 = Rules::preset("downtown").unwrap();
+        let app = App::new(100, h17.clone(), 1);
+        let hi_lo = Counter::new(Box::new(TagSystem::hi_lo()), h17.decks);
+        assert_eq!(None, IndexTable::default().true_count(&hi_lo, &app));
+        let h17_table = IndexTable {
+            dealer_hits_soft_17: true,
+            ..IndexTable::default()
+        };
+        assert_eq!(Some(0.0), h17_table.true_count(&hi_lo, &app));
+    }
+}
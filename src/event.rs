@@ -0,0 +1,63 @@
+use crate::app::{GameState, Outcome};
+use crate::cards::Card;
+
+/// Where a dealt card goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Seat {
+    Dealer,
+    /// Player hand, by index into `App::hands`
+    Player(usize),
+}
+
+/// Something that happened at the table. `App` makes every change to a round by emitting an
+/// event, so the stream is enough to drive the UI, logs and statistics or to rebuild the table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// The table was cleared for the next round
+    RoundReset,
+    /// The shoe was reshuffled after the cut card came out, or because it ran dry mid-round
+    Shuffle,
+    BetPlaced {
+        bet: u32,
+    },
+    /// A card left the shoe. The dealer's hole card is dealt face down.
+    CardDealt {
+        to: Seat,
+        card: Card,
+    },
+    HoleCardRevealed {
+        card: Card,
+    },
+    InsurancePlaced {
+        stake: u32,
+    },
+    /// The second card of a hand was moved into a new hand placed right after it
+    HandSplit {
+        hand: usize,
+    },
+    BetDoubled {
+        hand: usize,
+    },
+    /// Play moved on to the next hand after a split
+    HandActivated {
+        hand: usize,
+    },
+    /// A hand was settled for `amount` won (positive) or lost (negative)
+    HandSettled {
+        hand: usize,
+        outcome: Outcome,
+        amount: i64,
+    },
+    InsuranceSettled {
+        outcome: Outcome,
+        amount: i64,
+    },
+    /// The bank moved by `change` to a balance of `bank`
+    BankChanged {
+        bank: u32,
+        change: i64,
+    },
+    StateChanged {
+        state: GameState,
+    },
+}
@@ -0,0 +1,71 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::de::DeserializeOwned;
+
+/// Read a table, counting system or index table file. Files ending in `.json` are parsed as
+/// JSON, anything else as TOML.
+pub fn load_file<T: DeserializeOwned>(path: &Path) -> Result<T, FileError> {
+    let contents = fs::read_to_string(path).map_err(|err| FileError::Io {
+        path: path.to_path_buf(),
+        source: err,
+    })?;
+    let is_json = path.extension().is_some_and(|ext| ext == "json");
+    let parsed = if is_json {
+        parse_json(&contents)
+    } else {
+        parse_toml(&contents)
+    };
+    parsed.map_err(|message| FileError::invalid(path, message))
+}
+
+pub fn parse_toml<T: DeserializeOwned>(contents: &str) -> Result<T, String> {
+    toml::from_str(contents).map_err(|err| err.message().to_string())
+}
+
+pub fn parse_json<T: DeserializeOwned>(contents: &str) -> Result<T, String> {
+    serde_json::from_str(contents).map_err(|err| err.to_string())
+}
+
+/// Why a file could not be loaded. Wrapped by the error of each kind of file, which names it.
+#[derive(Debug)]
+pub enum FileError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// The file is not valid TOML/JSON, has unknown or mistyped fields, or its contents do not
+    /// make sense
+    Invalid {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl FileError {
+    pub fn invalid(path: &Path, message: impl Into<String>) -> FileError {
+        FileError::Invalid {
+            path: path.to_path_buf(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for FileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileError::Io { path, source } => {
+                write!(f, "file {} could not be read: {}", path.display(), source)
+            }
+            FileError::Invalid { path, message } => {
+                write!(f, "file {} is invalid: {}", path.display(), message.trim())
+            }
+        }
+    }
+}
+
+impl Error for FileError {}
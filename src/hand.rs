@@ -0,0 +1,129 @@
+use std::fmt::Display;
+
+use crate::cards::{Card, Rank};
+
+pub const BLACKJACK: u8 = 21;
+pub const ACE_HIGH: u8 = 11;
+pub const ACE_LOW: u8 = 1;
+pub const FACECARD: u8 = 10;
+
+/// Blackjack value of a set of cards. Aces are scored as 11 unless the total score is above 21,
+/// in which case they are scored as 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandValue {
+    pub total: u8,
+    /// An ace is still counted as 11
+    pub is_soft: bool,
+    /// Two cards making 21
+    pub is_blackjack: bool,
+    pub is_bust: bool,
+    /// Two cards of the same rank
+    pub is_pair: bool,
+}
+
+impl HandValue {
+    pub fn of(cards: &[Card]) -> HandValue {
+        let mut aces = 0;
+        let mut total = 0;
+        for card in cards {
+            if let Rank::Ace = card.rank {
+                aces += 1;
+            }
+            total += card_value(card);
+        }
+
+        // Adjust Aces value downward if necessary
+        while total > BLACKJACK && aces > 0 {
+            total -= ACE_HIGH - ACE_LOW; // note operator precedence
+            aces -= 1;
+        }
+
+        HandValue {
+            total,
+            is_soft: aces > 0,
+            is_blackjack: cards.len() == 2 && total == BLACKJACK,
+            is_bust: total > BLACKJACK,
+            is_pair: cards.len() == 2 && cards[0].rank == cards[1].rank,
+        }
+    }
+
+    /// Total with every ace counted as 1
+    pub fn hard_total(&self) -> u8 {
+        if self.is_soft {
+            self.total - (ACE_HIGH - ACE_LOW)
+        } else {
+            self.total
+        }
+    }
+}
+
+/// "Blackjack", "Bust (24)", "Soft 17" or plain "17"
+impl Display for HandValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_blackjack {
+            write!(f, "Blackjack")
+        } else if self.is_bust {
+            write!(f, "Bust ({})", self.total)
+        } else if self.is_soft && self.total < BLACKJACK {
+            write!(f, "Soft {}", self.total)
+        } else {
+            write!(f, "{}", self.total)
+        }
+    }
+}
+
+/// Value of a single card, counting aces as 11
+pub fn card_value(card: &Card) -> u8 {
+    match card.rank {
+        Rank::Ace => ACE_HIGH,
+        Rank::Pip(num) => num,
+        _ => FACECARD,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cards::Suit;
+
+    fn hand(ranks: &[Rank]) -> Vec<Card> {
+        ranks
+            .iter()
+            .map(|&rank| Card::new(rank, Suit::Clubs))
+            .collect()
+    }
+
+    #[test]
+    fn soft_and_hard() {
+        let value = HandValue::of(&hand(&[Rank::Ace, Rank::Pip(6)]));
+        assert_eq!(17, value.total);
+        assert!(value.is_soft);
+        assert_eq!(7, value.hard_total());
+        assert_eq!("Soft 17", value.to_string());
+
+        let value = HandValue::of(&hand(&[Rank::Ace, Rank::Pip(6), Rank::King]));
+        assert_eq!(17, value.total);
+        assert!(!value.is_soft);
+        assert_eq!("17", value.to_string());
+
+        let value = HandValue::of(&hand(&[Rank::Ace, Rank::Ace, Rank::Pip(9)]));
+        assert_eq!(21, value.total);
+        assert!(value.is_soft);
+        assert!(!value.is_blackjack);
+    }
+
+    #[test]
+    fn blackjack_bust_and_pairs() {
+        let value = HandValue::of(&hand(&[Rank::Ace, Rank::Queen]));
+        assert!(value.is_blackjack);
+        assert_eq!("Blackjack", value.to_string());
+
+        let value = HandValue::of(&hand(&[Rank::King, Rank::Queen, Rank::Pip(5)]));
+        assert!(value.is_bust);
+        assert_eq!("Bust (25)", value.to_string());
+
+        assert!(HandValue::of(&hand(&[Rank::Pip(8), Rank::Pip(8)])).is_pair);
+        assert!(!HandValue::of(&hand(&[Rank::King, Rank::Queen])).is_pair);
+        assert!(!HandValue::of(&hand(&[Rank::Pip(8), Rank::Pip(8), Rank::Pip(8)])).is_pair);
+    }
+}
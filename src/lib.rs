@@ -0,0 +1,45 @@
+//! Blackjack game engine behind the `tui-blackjack` terminal game.
+//!
+//! The library has no terminal dependencies, so simulations and bots can drive the same rules the
+//! game uses:
+//!
+//! ```
+//! use tui_blackjack::{App, Command, GameState, Rules};
+//!
+//! # fn main() -> Result<(), tui_blackjack::GameError> {
+//! let mut app = App::new(100, Rules::vegas_strip(), 42);
+//! app.place_bet(10)?;
+//! app.start()?;
+//! if let GameState::PlayerTurn = app.state {
+//!     app.run(Command::Stand)?;
+//! }
+//! while let GameState::DealerTurn = app.state {
+//!     app.run(Command::AdvanceDealer)?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod app;
+pub mod cards;
+pub mod count;
+pub mod deviation;
+pub mod event;
+pub mod file;
+pub mod hand;
+pub mod rules;
+pub mod strategy;
+pub mod table;
+pub mod trainer;
+
+pub use app::{App, Command, GameError, GameState, Hand, Outcome, ReplayError};
+pub use cards::{Card, Rank, Shoe, Suit};
+pub use count::{Counter, CountingSystem, TagSystem};
+pub use deviation::{Deviation, IndexTable};
+pub use event::{Event, Seat};
+pub use file::FileError;
+pub use hand::HandValue;
+pub use rules::Rules;
+pub use strategy::{Advice, BasicStrategy, IndexPlay};
+pub use table::{Table, TableError};
+pub use trainer::{Mistake, Trainer};
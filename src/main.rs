@@ -1,8 +1,15 @@
-mod app;
-mod cards;
+mod animation;
+mod card_view;
+mod cli;
+mod practice;
+mod theme;
 mod ui;
 
-use std::{error::Error, io, thread::sleep, time::Duration};
+use std::{
+    error::Error,
+    io,
+    time::{Duration, Instant},
+};
 
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
@@ -12,17 +19,33 @@ use crossterm::{
 
 use ratatui::{
     backend::{Backend, CrosstermBackend},
-    style::{Color, Style},
+    style::Style,
     widgets::{Block, Borders},
     Terminal,
 };
 
 use tui_textarea::TextArea;
 
-use crate::app::*;
-use crate::ui::ui;
+use tui_blackjack::app::*;
+
+use crate::animation::Animator;
+use crate::cli::{Args, USAGE};
+use crate::practice::{parse_count, Practice};
+use crate::theme::Theme;
+use crate::ui::{ui, Notice, PLAYER_KEYS};
+
+/// How often the screen is redrawn while waiting for input
+const TICK: Duration = Duration::from_millis(50);
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let args = match Args::parse() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
     // set up terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -32,8 +55,18 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut terminal = Terminal::new(backend)?;
 
     // create app and run it
-    let mut app = App::default();
-    let res = run_app(&mut app, &mut terminal);
+    let seed = args.seed.unwrap_or_else(|| fastrand::u64(..));
+    let mut app = App::new(args.table.bank, args.rules(), seed);
+    let mut animator = Animator::new(!args.no_animations);
+    let mut practice = Practice::new(&args, &app.rules);
+    let res = run_app(
+        &mut app,
+        &mut terminal,
+        &mut animator,
+        &mut practice,
+        args.dealer_speed.delay(),
+        &args.theme,
+    );
 
     // restore terminal
     disable_raw_mode()?;
@@ -51,65 +84,233 @@ fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-pub fn run_app<B: Backend>(app: &mut App, terminal: &mut Terminal<B>) -> io::Result<()> {
+pub fn run_app<B: Backend>(
+    app: &mut App,
+    terminal: &mut Terminal<B>,
+    animator: &mut Animator,
+    practice: &mut Practice,
+    dealer_delay: Duration,
+    theme: &Theme,
+) -> io::Result<()> {
     let mut textarea = TextArea::default();
+    let mut insurance_form = TextArea::default();
+    // Refused command or hint, shown until the next key press
+    let mut notice: Option<Notice> = None;
+    // When the dealer draws their next card
+    let mut dealer_due: Option<Instant> = None;
 
     loop {
-        let is_valid = validate(&mut textarea, app);
-        terminal.draw(|f| ui(f, app, &mut textarea))?;
+        let amount = match app.state {
+            GameState::Insurance => {
+                let title = format!("Insurance (up to ${})", app.max_insurance());
+                validate(&mut insurance_form, 1, app.max_insurance(), &title, theme)
+            }
+            _ => {
+                let max_bet = app.bank.min(app.rules.max_bet);
+                validate(
+                    &mut textarea,
+                    app.rules.min_bet,
+                    max_bet,
+                    "Place bet",
+                    theme,
+                )
+            }
+        };
+        let form = match app.state {
+            GameState::Insurance => &mut insurance_form,
+            _ => &mut textarea,
+        };
+        animator.update(app.events());
+        if let Some(counter) = practice.counter.as_mut() {
+            counter.update(app.events());
+        }
+        terminal.draw(|f| ui(f, app, form, notice.as_ref(), animator, practice, theme))?;
 
-        // Run dealer animation
-        if let GameState::DealerTurn = app.state {
-            sleep(Duration::from_secs(1));
-            app.run(Command::AdvanceDealer);
-            continue;
+        // Step the dealer on a timer so keys and resizes are still handled in between. The
+        // timer starts once the last card has landed.
+        if app.state == GameState::DealerTurn && animator.is_idle() {
+            let due = *dealer_due.get_or_insert_with(|| Instant::now() + dealer_delay);
+            if Instant::now() >= due {
+                if let Err(err) = app.run(Command::AdvanceDealer) {
+                    notice = Some(Notice::Refused(err));
+                }
+                dealer_due = None;
+                continue;
+            }
+        } else {
+            dealer_due = None;
         }
 
+        if !event::poll(TICK)? {
+            continue;
+        }
         if let Event::Key(key) = event::read()? {
             if key.kind == event::KeyEventKind::Release {
                 continue;
             }
-            match app.state {
+            // Any key finishes the animations and clears the last notice before it is handled
+            animator.skip();
+            if key.code == KeyCode::Tab && practice.counter.is_some() {
+                practice.show_count = !practice.show_count;
+                continue;
+            }
+            notice = None;
+            if let Some(quiz) = practice.quiz.as_mut() {
+                let result = match key.code {
+                    KeyCode::Enter => {
+                        let Some(guess) = parse_count(&quiz.lines()[0]) else {
+                            continue;
+                        };
+                        if let Some(counter) = practice.counter.as_mut() {
+                            let count = counter.running;
+                            counter.answer(guess);
+                            notice = Some(Notice::Quiz { guess, count });
+                        }
+                        practice.quiz = None;
+                        app.reset()
+                    }
+                    KeyCode::Esc => {
+                        practice.quiz = None;
+                        app.reset()
+                    }
+                    _ => {
+                        quiz.input(key);
+                        Ok(())
+                    }
+                };
+                if let Err(err) = result {
+                    notice = Some(Notice::Refused(err));
+                }
+                continue;
+            }
+            let legal = app.legal_actions();
+            let natural = app.player_hand().is_some_and(Hand::is_natural);
+            let result = match app.state {
                 GameState::EnterBet => {
                     match key.code {
                         KeyCode::Esc => break,
-                        KeyCode::Enter if is_valid => {
-                            let bet = textarea.lines()[0].parse::<u32>().unwrap();
-                            app.place_bet(bet);
-                            app.start();
-                        }
-                        KeyCode::Enter => {},
+                        KeyCode::Enter => match amount {
+                            Some(bet) => {
+                                insurance_form = TextArea::default();
+                                app.place_bet(bet).and_then(|_| app.start())
+                            }
+                            None => Ok(()),
+                        },
                         _ => {
                             // TextArea::input returns if the input modified its text
                             textarea.input(key); 
-                            
+                            Ok(())
                         }
                     }
                 }
+                GameState::Insurance if natural => match key.code {
+                    KeyCode::Char('q') => break,
+                    KeyCode::Char('?') => {
+                        notice = hint(app, practice);
+                        Ok(())
+                    }
+                    KeyCode::Char('y') => play(app, practice, Command::EvenMoney, &mut notice),
+                    KeyCode::Char('n') => play(app, practice, Command::Insurance(0), &mut notice),
+                    _ => Ok(()),
+                },
+                GameState::Insurance => match key.code {
+                    KeyCode::Char('?') => {
+                        notice = hint(app, practice);
+                        Ok(())
+                    }
+                    KeyCode::Esc => play(app, practice, Command::Insurance(0), &mut notice),
+                    KeyCode::Enter => match amount {
+                        Some(stake) => play(app, practice, Command::Insurance(stake), &mut notice),
+                        None => Ok(()),
+                    },
+                    _ => {
+                        insurance_form.input(key);
+                        Ok(())
+                    }
+                },
                 GameState::PlayerTurn => match key.code {
                     KeyCode::Char('q') => break,
-                    KeyCode::Char('h') => app.run(Command::Hit),
-                    KeyCode::Char('s') => app.run(Command::Stand),
-                    _ => {}
+                    KeyCode::Char('?') => {
+                        notice = hint(app, practice);
+                        Ok(())
+                    }
+                    KeyCode::Char(c) => match PLAYER_KEYS.iter().find(|(key, ..)| *key == c) {
+                        Some(&(_, command, _)) if legal.contains(&command) => {
+                            play(app, practice, command, &mut notice)
+                        }
+                        // Explain why a greyed-out move is unavailable
+                        Some(&(_, command, _)) => app.check(command),
+                        None => Ok(()),
+                    },
+                    _ => Ok(()),
                 },
                 GameState::DealerTurn => {
                     if let KeyCode::Char('q') = key.code {
                         break;
                     }
+                    Ok(())
                 }
                 // Handle both win and lose cases
                 _ => match key.code {
-                    KeyCode::Enter => app.reset(),
+                    KeyCode::Enter => {
+                        // Counting practice sometimes asks for the count before the next round
+                        if practice.maybe_quiz(theme) {
+                            Ok(())
+                        } else {
+                            app.reset()
+                        }
+                    }
                     KeyCode::Char('q') => break,
-                    _ => {}
+                    _ => Ok(()),
                 },
+            };
+            if let Err(err) = result {
+                notice = Some(Notice::Refused(err));
             }
         }
     }
     Ok(())
 }
 
-fn validate(textarea: &mut TextArea, app: &App) -> bool {
+/// Basic strategy's advice for the decision at hand, with index plays in counting practice
+fn hint(app: &App, practice: &Practice) -> Option<Notice> {
+    practice
+        .strategy
+        .advise(app, practice.true_count(app))
+        .map(Notice::Hint)
+}
+
+/// Make a play, grading it first in trainer mode. A mistake is flagged in `notice`, and not made
+/// at all when mistakes are blocked.
+fn play(
+    app: &mut App,
+    practice: &mut Practice,
+    command: Command,
+    notice: &mut Option<Notice>,
+) -> Result<(), GameError> {
+    let true_count = practice.true_count(app);
+    let Some(trainer) = practice.trainer.as_mut() else {
+        return app.run(command);
+    };
+    let mistake = trainer.grade(app, command, true_count);
+    let blocked = mistake.is_some() && trainer.block_mistakes;
+    *notice = mistake.map(Notice::Mistake);
+    if blocked {
+        Ok(())
+    } else {
+        app.run(command)
+    }
+}
+
+/// Check the amount typed into `textarea` lies within `min..=max`, styling the form to match.
+/// Returns the amount when it is valid.
+fn validate(
+    textarea: &mut TextArea,
+    min: u32,
+    max: u32,
+    title: &str,
+    theme: &Theme,
+) -> Option<u32> {
     let bet = textarea.lines()[0].parse::<u32>();
 
     if textarea.is_empty() {
@@ -118,52 +319,52 @@ fn validate(textarea: &mut TextArea, app: &App) -> bool {
         textarea.set_block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Place bet")
-                .border_style(Style::default().fg(Color::Yellow)),
+                .title(title.to_string())
+                .border_style(Style::default().fg(theme.accent)),
         );
-        return false
+        return None;
     }
 
     match bet {
         Ok(bet) => {
-            if bet > app.bank {
-                textarea.set_style(Style::default().fg(Color::LightRed));
+            if bet > max {
+                textarea.set_style(Style::default().fg(theme.lose));
                 textarea.set_block(
                     Block::default()
                         .borders(Borders::ALL)
                         .title("Error: Too big!")
-                        .border_style(Style::default().fg(Color::LightRed)),
+                        .border_style(Style::default().fg(theme.lose)),
                 );
-                false
-            } else if bet == 0 {
-                textarea.set_style(Style::default().fg(Color::LightRed));
+                None
+            } else if bet < min {
+                textarea.set_style(Style::default().fg(theme.lose));
                 textarea.set_block(
                     Block::default()
                         .borders(Borders::ALL)
-                        .title("Error: Bet must be greater than 0")
-                        .border_style(Style::default().fg(Color::LightRed)),
+                        .title(format!("Error: Minimum is ${}", min))
+                        .border_style(Style::default().fg(theme.lose)),
                 );
-                false
+                None
             } else {
-                textarea.set_style(Style::default().fg(Color::LightGreen));
+                textarea.set_style(Style::default().fg(theme.win));
                 textarea.set_block(
                     Block::default()
                         .borders(Borders::ALL)
                         .title("OK")
-                        .border_style(Style::default().fg(Color::LightGreen)),
+                        .border_style(Style::default().fg(theme.win)),
                 );
-                true
+                Some(bet)
             }
         }
         Err(_) => {
-            textarea.set_style(Style::default().fg(Color::LightRed));
+            textarea.set_style(Style::default().fg(theme.lose));
             textarea.set_block(
                 Block::default()
                     .borders(Borders::ALL)
                     .title("Error: Invalid input")
-                    .border_style(Style::default().fg(Color::LightRed)),
+                    .border_style(Style::default().fg(theme.lose)),
             );
-            false
+            None
         }
     }
 }
@@ -0,0 +1,84 @@
+use ratatui::{
+    style::Style,
+    widgets::{Block, Borders},
+};
+use tui_textarea::TextArea;
+
+use tui_blackjack::app::App;
+use tui_blackjack::count::Counter;
+use tui_blackjack::rules::Rules;
+use tui_blackjack::strategy::BasicStrategy;
+use tui_blackjack::trainer::Trainer;
+
+use crate::cli::Args;
+use crate::theme::Theme;
+
+/// One round in this many ends with a running count quiz
+const QUIZ_ODDS: u8 = 3;
+
+/// Training aids picked on the command line and what they remember between rounds
+pub struct Practice<'a> {
+    pub strategy: BasicStrategy,
+    pub trainer: Option<Trainer>,
+    /// Running count kept in counting practice
+    pub counter: Option<Counter>,
+    /// Show the running and true count in the stats panel
+    pub show_count: bool,
+    /// Answer form while a running count quiz is open
+    pub quiz: Option<TextArea<'a>>,
+}
+
+impl<'a> Practice<'a> {
+    pub fn new(args: &Args, rules: &Rules) -> Self {
+        let strategy = BasicStrategy::new(rules).with_indices(args.indices.clone());
+        let trainer = args
+            .trainer
+            .then(|| Trainer::new(strategy.clone(), args.block_mistakes));
+        Practice {
+            strategy,
+            trainer,
+            counter: args
+                .count
+                .then(|| Counter::new(Box::new(args.count_system.clone()), rules.decks)),
+            show_count: true,
+            quiz: None,
+        }
+    }
+
+    /// In counting practice, open a running count quiz at random. Returns whether one opened.
+    pub fn maybe_quiz(&mut self, theme: &Theme) -> bool {
+        if self.counter.is_none() || fastrand::u8(..QUIZ_ODDS) != 0 {
+            return false;
+        }
+        let mut form = TextArea::default();
+        form.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("What is the running count?")
+                .border_style(Style::default().fg(theme.accent)),
+        );
+        self.quiz = Some(form);
+        true
+    }
+
+    /// True count for index plays, kept in counting practice with a balanced system and at a
+    /// table that the index table was made for. Otherwise hints and grading follow the chart
+    /// alone.
+    pub fn true_count(&self, app: &App) -> Option<f32> {
+        let indices = self.strategy.indices();
+        self.counter
+            .as_ref()
+            .and_then(|counter| indices.true_count(counter, app))
+    }
+
+    /// The count is on show: counting practice is on, the count is not hidden and no quiz is open
+    pub fn count_shown(&self) -> bool {
+        self.show_count && self.quiz.is_none()
+    }
+}
+
+/// Read a count typed as "3", "+3", "-3" or "-1.5"
+pub fn parse_count(text: &str) -> Option<f32> {
+    let text = text.trim();
+    text.strip_prefix('+').unwrap_or(text).parse().ok()
+}
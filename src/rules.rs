@@ -0,0 +1,258 @@
+use std::fmt::Display;
+
+use serde::Deserialize;
+
+use crate::cards::{MAX_DECKS, MAX_PENETRATION, MIN_DECKS, MIN_PENETRATION};
+
+pub const DEFAULT_DECKS: u8 = 6;
+pub const DEFAULT_PENETRATION: u8 = 75;
+
+/// Names accepted by `Rules::preset`
+pub const PRESETS: [&str; 4] = ["vegas-strip", "atlantic-city", "downtown", "european"];
+
+/// House rules for the table. Consulted by `App` whenever a rule decides whether an action is
+/// allowed. Fields missing from a table file fall back to the defaults.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    /// Name shown in the title bar
+    pub name: String,
+    pub decks: u8,
+    /// Percentage of the shoe dealt before the cut card comes out
+    pub penetration: u8,
+    /// Dealer draws to a soft 17 instead of standing on it
+    pub dealer_hits_soft_17: bool,
+    pub blackjack_payout: BlackjackPayout,
+    pub min_bet: u32,
+    pub max_bet: u32,
+    /// Maximum number of hands the player may hold after splitting and resplitting
+    pub max_hands: u8,
+    /// Split aces may be split again if another ace is dealt to them
+    pub resplit_aces: bool,
+    /// Split aces receive exactly one card each and may not be hit
+    pub split_aces_one_card: bool,
+    /// Any two ten-value cards may be split (e.g. K and J), not only true pairs
+    pub split_unlike_tens: bool,
+    /// Which two-card totals may be doubled
+    pub double: DoubleRule,
+    /// Hands created by a split may be doubled
+    pub double_after_split: bool,
+    pub surrender: SurrenderRule,
+    /// European no-hole-card: the dealer's second card is only dealt after the player acts, so
+    /// there is no peek for blackjack
+    pub no_hole_card: bool,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            name: String::from("House rules"),
+            decks: DEFAULT_DECKS,
+            penetration: DEFAULT_PENETRATION,
+            dealer_hits_soft_17: false,
+            blackjack_payout: BlackjackPayout::ThreeToTwo,
+            min_bet: 1,
+            max_bet: 1000,
+            max_hands: 4,
+            resplit_aces: false,
+            split_aces_one_card: true,
+            split_unlike_tens: true,
+            double: DoubleRule::AnyTwo,
+            double_after_split: true,
+            surrender: SurrenderRule::None,
+            no_hole_card: false,
+        }
+    }
+}
+
+impl Rules {
+    /// Look up one of the named `PRESETS`
+    pub fn preset(name: &str) -> Option<Rules> {
+        match name {
+            "vegas-strip" => Some(Rules::vegas_strip()),
+            "atlantic-city" => Some(Rules::atlantic_city()),
+            "downtown" => Some(Rules::downtown()),
+            "european" => Some(Rules::european()),
+            _ => None,
+        }
+    }
+
+    /// Six-deck shoe, dealer stands on soft 17, late surrender
+    pub fn vegas_strip() -> Rules {
+        Rules {
+            name: String::from("Vegas Strip"),
+            min_bet: 10,
+            max_bet: 5000,
+            surrender: SurrenderRule::Late,
+            ..Rules::default()
+        }
+    }
+
+    /// Eight-deck shoe, dealer stands on soft 17, late surrender
+    pub fn atlantic_city() -> Rules {
+        Rules {
+            name: String::from("Atlantic City"),
+            decks: 8,
+            min_bet: 10,
+            max_bet: 2000,
+            surrender: SurrenderRule::Late,
+            ..Rules::default()
+        }
+    }
+
+    /// Double deck, dealer hits soft 17 and blackjack only pays 6:5
+    pub fn downtown() -> Rules {
+        Rules {
+            name: String::from("Downtown 6:5"),
+            decks: 2,
+            penetration: 65,
+            dealer_hits_soft_17: true,
+            blackjack_payout: BlackjackPayout::SixToFive,
+            min_bet: 5,
+            max_bet: 500,
+            ..Rules::default()
+        }
+    }
+
+    /// No hole card, doubling on 9 to 11 only and a single split
+    pub fn european() -> Rules {
+        Rules {
+            name: String::from("European"),
+            min_bet: 5,
+            max_bet: 1000,
+            max_hands: 2,
+            double: DoubleRule::NineToEleven,
+            double_after_split: false,
+            split_unlike_tens: false,
+            no_hole_card: true,
+            ..Rules::default()
+        }
+    }
+
+    /// Reject combinations of rules that cannot be played
+    pub fn validate(&self) -> Result<(), String> {
+        if !(MIN_DECKS..=MAX_DECKS).contains(&self.decks) {
+            return Err(format!(
+                "decks must be from {} to {}, got {}",
+                MIN_DECKS, MAX_DECKS, self.decks
+            ));
+        }
+        if !(MIN_PENETRATION..=MAX_PENETRATION).contains(&self.penetration) {
+            return Err(format!(
+                "penetration must be from {}% to {}%, got {}%",
+                MIN_PENETRATION, MAX_PENETRATION, self.penetration
+            ));
+        }
+        if self.min_bet == 0 {
+            return Err(String::from("min_bet must be at least 1"));
+        }
+        if self.min_bet > self.max_bet {
+            return Err(format!(
+                "min_bet (${}) is above max_bet (${})",
+                self.min_bet, self.max_bet
+            ));
+        }
+        if self.max_hands == 0 {
+            return Err(String::from("max_hands must be at least 1"));
+        }
+        if self.resplit_aces && self.max_hands < 3 {
+            return Err(String::from(
+                "resplit_aces needs max_hands of at least 3 to resplit",
+            ));
+        }
+        if self.surrender == SurrenderRule::Late && self.no_hole_card {
+            return Err(String::from(
+                "late surrender comes after the dealer peeks, but no_hole_card tables never peek",
+            ));
+        }
+        Ok(())
+    }
+
+    /// One-line description of the main rules, e.g. "Vegas Strip: 6 decks, S17, BJ pays 3:2"
+    pub fn summary(&self) -> String {
+        let soft_17 = if self.dealer_hits_soft_17 {
+            "H17"
+        } else {
+            "S17"
+        };
+        let mut parts = vec![
+            format!("{} decks", self.decks),
+            String::from(soft_17),
+            format!("BJ pays {}", self.blackjack_payout),
+        ];
+        if self.double_after_split {
+            parts.push(String::from("DAS"));
+        }
+        match self.surrender {
+            SurrenderRule::None => {}
+            SurrenderRule::Late => parts.push(String::from("LS")),
+            SurrenderRule::Early => parts.push(String::from("ES")),
+        }
+        if self.no_hole_card {
+            parts.push(String::from("ENHC"));
+        }
+        parts.push(format!("${}-${}", self.min_bet, self.max_bet));
+        format!("{}: {}", self.name, parts.join(", "))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum BlackjackPayout {
+    #[serde(rename = "3:2")]
+    ThreeToTwo,
+    #[serde(rename = "6:5")]
+    SixToFive,
+    #[serde(rename = "1:1")]
+    OneToOne,
+}
+
+impl BlackjackPayout {
+    /// Winnings on a natural for the given bet, rounded down to the dollar
+    pub fn pay(&self, bet: u32) -> u32 {
+        match self {
+            BlackjackPayout::ThreeToTwo => bet * 3 / 2,
+            BlackjackPayout::SixToFive => bet * 6 / 5,
+            BlackjackPayout::OneToOne => bet,
+        }
+    }
+}
+
+impl Display for BlackjackPayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlackjackPayout::ThreeToTwo => write!(f, "3:2"),
+            BlackjackPayout::SixToFive => write!(f, "6:5"),
+            BlackjackPayout::OneToOne => write!(f, "1:1"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DoubleRule {
+    AnyTwo,
+    NineToEleven,
+    TenToEleven,
+}
+
+impl DoubleRule {
+    /// Whether a two-card hand with the given total may be doubled
+    pub fn allows(&self, score: u8) -> bool {
+        match self {
+            DoubleRule::AnyTwo => true,
+            DoubleRule::NineToEleven => (9..=11).contains(&score),
+            DoubleRule::TenToEleven => (10..=11).contains(&score),
+        }
+    }
+}
+
+/// Whether the player may give up half their bet instead of playing the hand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SurrenderRule {
+    None,
+    /// Only after the dealer has checked for blackjack
+    Late,
+    /// Before the dealer checks for blackjack
+    Early,
+}
@@ -0,0 +1,603 @@
+use std::{collections::HashMap, fmt::Display};
+
+use crate::app::{App, Command, GameState, Hand};
+use crate::cards::{DECK_SIZE, SUITS};
+use crate::deviation::IndexTable;
+use crate::hand::{card_value, ACE_HIGH, ACE_LOW, BLACKJACK, FACECARD};
+use crate::rules::{Rules, SurrenderRule};
+
+/// Dealer upcards from two to ace, as returned by `App::dealer_showing`
+const UPCARDS: std::ops::RangeInclusive<u8> = 2..=ACE_HIGH;
+
+/// Row of a basic strategy chart: how the player's hand is classified
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Row {
+    Hard(u8),
+    Soft(u8),
+    /// Two cards of the same value, by the value of one card (11 for aces)
+    Pair(u8),
+}
+
+impl Row {
+    pub fn of(hand: &Hand) -> Row {
+        let value = hand.value();
+        match hand.cards.as_slice() {
+            [first, second] if card_value(first) == card_value(second) => {
+                Row::Pair(card_value(first))
+            }
+            _ if value.is_soft => Row::Soft(value.total),
+            _ => Row::Hard(value.total),
+        }
+    }
+
+    /// Every row of the chart
+    fn all() -> impl Iterator<Item = Row> {
+        (5..=BLACKJACK)
+            .map(Row::Hard)
+            .chain((13..=BLACKJACK).map(Row::Soft))
+            .chain((2..=ACE_HIGH).map(Row::Pair))
+    }
+
+    /// Cards of a typical hand in this row, by value with aces as 1
+    fn cards(&self) -> Vec<u8> {
+        match *self {
+            Row::Hard(BLACKJACK) => vec![FACECARD, FACECARD, ACE_LOW],
+            Row::Hard(total) if total > FACECARD + 1 => vec![FACECARD, total - FACECARD],
+            Row::Hard(total) => vec![(total - 1) / 2, total - (total - 1) / 2],
+            Row::Soft(total) => vec![ACE_LOW, total - ACE_HIGH],
+            Row::Pair(ACE_HIGH) => vec![ACE_LOW, ACE_LOW],
+            Row::Pair(value) => vec![value, value],
+        }
+    }
+}
+
+impl Display for Row {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Row::Hard(total) => write!(f, "{}", total),
+            Row::Soft(total) => write!(f, "soft {}", total),
+            Row::Pair(ACE_HIGH) => write!(f, "A,A"),
+            Row::Pair(value) => write!(f, "{},{}", value, value),
+        }
+    }
+}
+
+/// Basic strategy for one set of table rules. Every cell of the chart ranks the plays by their
+/// expected value, worked out for the number of decks, the dealer's soft 17 rule, doubling after
+/// splits, surrender and whether the dealer peeks for blackjack. Given a true count, the plays
+/// in its index table override the chart.
+#[derive(Debug, Clone)]
+pub struct BasicStrategy {
+    cells: HashMap<(Row, u8), Vec<(Command, f64)>>,
+    indices: IndexTable,
+}
+
+impl BasicStrategy {
+    pub fn new(rules: &Rules) -> BasicStrategy {
+        let mut cells = HashMap::new();
+        for row in Row::all() {
+            for upcard in UPCARDS {
+                let plays = Evaluator::new(rules, &row.cards(), upcard).plays(row);
+                cells.insert((row, upcard), plays);
+            }
+        }
+        BasicStrategy {
+            cells,
+            indices: IndexTable::default(),
+        }
+    }
+
+    /// Replace the bundled Illustrious 18 and Fab 4 index plays
+    pub fn with_indices(self, indices: IndexTable) -> BasicStrategy {
+        BasicStrategy { indices, ..self }
+    }
+
+    pub fn indices(&self) -> &IndexTable {
+        &self.indices
+    }
+
+    /// Every play on the hand against the dealer's upcard with its expected value per unit bet,
+    /// best first. Plays the table may not allow on this particular hand are included.
+    pub fn plays(&self, hand: &Hand, dealer_showing: u8) -> &[(Command, f64)] {
+        self.cells
+            .get(&(Row::of(hand), dealer_showing))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// The legal plays on the active hand, or whether to take insurance, best first. `None`
+    /// outside the player's turn and the insurance offer. With a true count, an index play that
+    /// disagrees with the chart is added.
+    pub fn advise(&self, app: &App, true_count: Option<f32>) -> Option<Advice> {
+        let legal = app.legal_actions();
+        let plays: Vec<(Command, f64)> = match app.state {
+            GameState::PlayerTurn => self
+                .plays(app.player_hand()?, app.dealer_showing())
+                .iter()
+                .filter(|(command, _)| legal.contains(command))
+                .copied()
+                .collect(),
+            GameState::Insurance => insurance_plays(app, app.player_hand()?, &legal),
+            _ => return None,
+        };
+        if plays.is_empty() {
+            return None;
+        }
+        let mut advice = Advice {
+            plays,
+            index_play: None,
+        };
+        if let Some(true_count) = true_count {
+            advice.index_play = self.index_play(app, &advice, true_count);
+        }
+        Some(advice)
+    }
+
+    /// The index table's play at this true count, when it differs from the chart. An entry is
+    /// only used when both of its plays are legal and one of them is the chart's.
+    fn index_play(&self, app: &App, advice: &Advice, true_count: f32) -> Option<IndexPlay> {
+        let chart = advice.plays[0].0;
+        let (index, play) = if app.state == GameState::Insurance {
+            let index = self.indices.insurance?;
+            let take = advice.plays.iter().find(|(play, _)| *play != DECLINE)?.0;
+            (index, if true_count >= index { take } else { DECLINE })
+        } else {
+            let hand = app.player_hand()?;
+            let legal = |command| advice.plays.iter().any(|(play, _)| *play == command);
+            let deviation = self.indices.deviations.iter().find(|deviation| {
+                deviation.covers(hand, app.dealer_showing())
+                    && legal(deviation.play)
+                    && legal(deviation.otherwise)
+                    && (chart == deviation.play || chart == deviation.otherwise)
+            })?;
+            (deviation.index, deviation.play_at(true_count))
+        };
+        (play != chart).then_some(IndexPlay {
+            play,
+            index,
+            true_count,
+        })
+    }
+}
+
+const DECLINE: Command = Command::Insurance(0);
+
+/// Taking insurance (or even money on a blackjack) against declining it, valued per unit of the
+/// hand's bet with the largest stake. Like the chart, this assumes a fresh shoe.
+fn insurance_plays(app: &App, hand: &Hand, legal: &[Command]) -> Vec<(Command, f64)> {
+    let decks = app.rules.decks as f64;
+    let cards = &hand.cards;
+    let tens_seen = cards
+        .iter()
+        .filter(|card| card_value(card) == FACECARD)
+        .count() as f64;
+    let tens = 4.0 * SUITS.len() as f64 * decks - tens_seen;
+    // The dealer's ace is out of the shoe as well
+    let unseen = DECK_SIZE as f64 * decks - cards.len() as f64 - 1.0;
+    // Insurance pays 2:1 on half the bet
+    let take_ev = (3.0 * tens / unseen - 1.0) / 2.0;
+    let take = if legal.contains(&Command::EvenMoney) {
+        Some(Command::EvenMoney)
+    } else {
+        legal.iter().copied().find(|command| *command != DECLINE)
+    };
+    let mut plays = vec![(DECLINE, 0.0)];
+    plays.extend(take.map(|take| (take, take_ev)));
+    plays.sort_by(|a, b| b.1.total_cmp(&a.1));
+    plays
+}
+
+/// Basic strategy's ranking of the plays open to the active hand
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advice {
+    /// Legal plays with their expected value per unit bet, best first
+    pub plays: Vec<(Command, f64)>,
+    /// Index play that overrides the chart at the current true count
+    pub index_play: Option<IndexPlay>,
+}
+
+/// A play from the index table, made because the true count crossed its index
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IndexPlay {
+    pub play: Command,
+    pub index: f32,
+    pub true_count: f32,
+}
+
+impl Advice {
+    /// The index play if there is one, otherwise the chart's best play
+    pub fn best(&self) -> Command {
+        self.index_play
+            .map_or(self.plays[0].0, |index_play| index_play.play)
+    }
+
+    /// Expected value given up by playing `command` instead of the chart's best play, per unit
+    /// bet
+    pub fn cost(&self, command: Command) -> Option<f64> {
+        self.plays
+            .iter()
+            .find(|(play, _)| *play == command)
+            .map(|(_, ev)| self.plays[0].1 - ev)
+    }
+}
+
+/// Works out the value of each play for one hand against one upcard. Cards are drawn from a fresh
+/// shoe with the player's cards and the upcard taken out.
+struct Evaluator<'a> {
+    rules: &'a Rules,
+    /// Chance of drawing each value, aces first at index 0
+    draw: [f64; 10],
+    /// Dealer's final totals from 17 to 21, then bust, given no dealer blackjack
+    dealer: [f64; 6],
+    /// Chance the dealer holds a blackjack the player has not been told about
+    unseen_blackjack: f64,
+    best: HashMap<(u8, bool), f64>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn new(rules: &'a Rules, cards: &[u8], upcard: u8) -> Evaluator<'a> {
+        let upcard = if upcard == ACE_HIGH { ACE_LOW } else { upcard };
+        let decks = rules.decks as f64;
+        let per_rank = SUITS.len() as f64 * decks;
+        let mut counts = [per_rank; 10];
+        counts[9] = per_rank * 4.0;
+        for &card in cards.iter().chain([upcard].iter()) {
+            counts[card as usize - 1] -= 1.0;
+        }
+        let total = DECK_SIZE as f64 * decks - cards.len() as f64 - 1.0;
+        let draw = counts.map(|count| count / total);
+
+        // Chance the hole card completes a blackjack
+        let blackjack = match upcard {
+            ACE_LOW => draw[9],
+            FACECARD => draw[0],
+            _ => 0.0,
+        };
+        let mut dealer = [0.0; 6];
+        let mut memo = HashMap::new();
+        let (total, soft) = add((0, false), upcard);
+        for value in 1..=10 {
+            let (hole_total, hole_soft) = add((total, soft), value);
+            if hole_total == BLACKJACK {
+                continue;
+            }
+            let outcomes = dealer_outcomes((hole_total, hole_soft), &draw, rules, &mut memo);
+            for (sum, outcome) in dealer.iter_mut().zip(outcomes) {
+                *sum += draw[value as usize - 1] * outcome / (1.0 - blackjack);
+            }
+        }
+        // Without a peek the blackjack is only found after the player acts. Early surrender is
+        // decided before the peek as well.
+        let unseen = rules.no_hole_card || rules.surrender == SurrenderRule::Early;
+
+        Evaluator {
+            rules,
+            draw,
+            dealer,
+            unseen_blackjack: if unseen { blackjack } else { 0.0 },
+            best: HashMap::new(),
+        }
+    }
+
+    /// Every play on the row's hand, best first
+    fn plays(&mut self, row: Row) -> Vec<(Command, f64)> {
+        let (total, soft) = row
+            .cards()
+            .iter()
+            .fold((0, false), |hand, &card| add(hand, card));
+        let mut plays = vec![
+            (Command::Hit, self.hit(total, soft)),
+            (Command::Stand, self.stand(total)),
+        ];
+        if row.cards().len() == 2 {
+            plays.push((Command::Double, self.double(total, soft)));
+        }
+        if let Row::Pair(value) = row {
+            plays.push((Command::Split, self.split(value)));
+        }
+
+        // A dealer blackjack takes the whole stake, doubled and split bets included when there
+        // was no hole card to peek at
+        let blackjack = self.unseen_blackjack;
+        for (command, ev) in plays.iter_mut() {
+            let stake = match command {
+                Command::Double | Command::Split if self.rules.no_hole_card => 2.0,
+                _ => 1.0,
+            };
+            *ev = blackjack * -stake + (1.0 - blackjack) * *ev;
+        }
+        if self.rules.surrender != SurrenderRule::None && row.cards().len() == 2 {
+            plays.push((Command::Surrender, -0.5));
+        }
+        plays.sort_by(|a, b| b.1.total_cmp(&a.1));
+        plays
+    }
+
+    fn stand(&self, total: u8) -> f64 {
+        if total > BLACKJACK {
+            return -1.0;
+        }
+        let bust = self.dealer[5];
+        let dealer = (17..=BLACKJACK).zip(self.dealer).map(|(dealer, chance)| {
+            if total > dealer {
+                chance
+            } else if total < dealer {
+                -chance
+            } else {
+                0.0
+            }
+        });
+        bust + dealer.sum::<f64>()
+    }
+
+    fn hit(&mut self, total: u8, soft: bool) -> f64 {
+        (1..=10)
+            .map(|card| {
+                let (total, soft) = add((total, soft), card);
+                self.draw[card as usize - 1] * self.hit_or_stand(total, soft)
+            })
+            .sum()
+    }
+
+    /// Value of the hand when the player keeps hitting for as long as it pays
+    fn hit_or_stand(&mut self, total: u8, soft: bool) -> f64 {
+        if total > BLACKJACK {
+            return -1.0;
+        }
+        if let Some(&ev) = self.best.get(&(total, soft)) {
+            return ev;
+        }
+        let ev = self.stand(total).max(self.hit(total, soft));
+        self.best.insert((total, soft), ev);
+        ev
+    }
+
+    fn double(&self, total: u8, soft: bool) -> f64 {
+        let ev: f64 = (1..=10)
+            .map(|card| self.draw[card as usize - 1] * self.stand(add((total, soft), card).0))
+            .sum();
+        2.0 * ev
+    }
+
+    /// Two hands each starting from one card of the pair. Resplits are not counted.
+    fn split(&mut self, value: u8) -> f64 {
+        let first = if value == ACE_HIGH { ACE_LOW } else { value };
+        let start = add((0, false), first);
+        let ev: f64 = (1..=10)
+            .map(|card| {
+                let (total, soft) = add(start, card);
+                let ev = if first == ACE_LOW && self.rules.split_aces_one_card {
+                    self.stand(total)
+                } else {
+                    let mut ev = self.hit_or_stand(total, soft);
+                    if self.rules.double_after_split && self.rules.double.allows(total) {
+                        ev = ev.max(self.double(total, soft));
+                    }
+                    ev
+                };
+                self.draw[card as usize - 1] * ev
+            })
+            .sum();
+        2.0 * ev
+    }
+}
+
+/// Add a card, by value with aces as 1, to a hand given as its total and whether it is soft
+fn add((total, soft): (u8, bool), card: u8) -> (u8, bool) {
+    let mut total = total + card;
+    let mut soft = soft;
+    if card == ACE_LOW && total + ACE_HIGH - ACE_LOW <= BLACKJACK {
+        total += ACE_HIGH - ACE_LOW;
+        soft = true;
+    }
+    if total > BLACKJACK && soft {
+        total -= ACE_HIGH - ACE_LOW;
+        soft = false;
+    }
+    (total, soft)
+}
+
+/// Chance of each final dealer total from 17 to 21, then bust, drawing from the given hand
+fn dealer_outcomes(
+    (total, soft): (u8, bool),
+    draw: &[f64; 10],
+    rules: &Rules,
+    memo: &mut HashMap<(u8, bool), [f64; 6]>,
+) -> [f64; 6] {
+    if let Some(&outcomes) = memo.get(&(total, soft)) {
+        return outcomes;
+    }
+    let mut outcomes = [0.0; 6];
+    if total > BLACKJACK {
+        outcomes[5] = 1.0;
+        return outcomes;
+    }
+    let hits_soft_17 = total == 17 && soft && rules.dealer_hits_soft_17;
+    if total >= 17 && !hits_soft_17 {
+        outcomes[total as usize - 17] = 1.0;
+        return outcomes;
+    }
+    for card in 1..=10 {
+        let next = dealer_outcomes(add((total, soft), card), draw, rules, memo);
+        for (sum, outcome) in outcomes.iter_mut().zip(next) {
+            *sum += draw[card as usize - 1] * outcome;
+        }
+    }
+    memo.insert((total, soft), outcomes);
+    outcomes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cards::{Card, Rank, Suit};
+    use crate::deviation::IndexTable;
+
+    fn best(strategy: &BasicStrategy, ranks: &[Rank], upcard: u8, legal: &[Command]) -> Command {
+        let cards = ranks
+            .iter()
+            .map(|&rank| Card::new(rank, Suit::Clubs))
+            .collect();
+        let hand = Hand::new(cards, 10);
+        strategy
+            .plays(&hand, upcard)
+            .iter()
+            .find(|(command, _)| legal.contains(command))
+            .unwrap()
+            .0
+    }
+
+    const ALL: [Command; 5] = [
+        Command::Hit,
+        Command::Stand,
+        Command::Double,
+        Command::Split,
+        Command::Surrender,
+    ];
+
+    #[test]
+    fn multi_deck_chart() {
+        let strategy = BasicStrategy::new(&Rules::default());
+        let cases = [
+            (vec![Rank::King, Rank::Pip(2)], 2, Command::Hit),
+            (vec![Rank::King, Rank::Pip(2)], 4, Command::Stand),
+            (vec![Rank::King, Rank::Pip(6)], 6, Command::Stand),
+            (vec![Rank::King, Rank::Pip(6)], 7, Command::Hit),
+            (vec![Rank::Pip(5), Rank::Pip(6)], 6, Command::Double),
+            (vec![Rank::Pip(4), Rank::Pip(5)], 7, Command::Hit),
+            (vec![Rank::Pip(8), Rank::Pip(8)], 10, Command::Split),
+            (vec![Rank::Ace, Rank::Ace], ACE_HIGH, Command::Split),
+            (vec![Rank::King, Rank::Queen], 6, Command::Stand),
+            (vec![Rank::Pip(9), Rank::Pip(9)], 7, Command::Stand),
+            (vec![Rank::Ace, Rank::Pip(7)], 9, Command::Hit),
+            (vec![Rank::Ace, Rank::Pip(7)], 6, Command::Double),
+            (vec![Rank::Ace, Rank::Pip(7)], 2, Command::Stand),
+            (vec![Rank::Pip(5), Rank::Pip(5)], 9, Command::Double),
+        ];
+        for (ranks, upcard, expected) in cases {
+            assert_eq!(
+                expected,
+                best(&strategy, &ranks, upcard, &ALL),
+                "{:?} against {}",
+                ranks,
+                upcard
+            );
+        }
+        // Double falls back to hitting once the hand has three cards
+        let three_cards = [Rank::Pip(2), Rank::Pip(3), Rank::Pip(6)];
+        assert_eq!(
+            Command::Hit,
+            best(&strategy, &three_cards, 6, &[Command::Hit, Command::Stand])
+        );
+    }
+
+    #[test]
+    fn chart_follows_rules() {
+        let fours = [Rank::Pip(4), Rank::Pip(4)];
+        let das = BasicStrategy::new(&Rules::default());
+        assert_eq!(Command::Split, best(&das, &fours, 5, &ALL));
+        let no_das = BasicStrategy::new(&Rules {
+            double_after_split: false,
+            ..Rules::default()
+        });
+        assert_eq!(Command::Hit, best(&no_das, &fours, 5, &ALL));
+
+        let eleven = [Rank::Pip(9), Rank::Pip(2)];
+        let s17 = BasicStrategy::new(&Rules::default());
+        assert_eq!(Command::Hit, best(&s17, &eleven, ACE_HIGH, &ALL));
+        let h17 = BasicStrategy::new(&Rules {
+            dealer_hits_soft_17: true,
+            ..Rules::default()
+        });
+        assert_eq!(Command::Double, best(&h17, &eleven, ACE_HIGH, &ALL));
+
+        let sixteen = [Rank::King, Rank::Pip(6)];
+        let late = BasicStrategy::new(&Rules::vegas_strip());
+        assert_eq!(Command::Surrender, best(&late, &sixteen, 10, &ALL));
+        assert_eq!(Command::Hit, best(&s17, &sixteen, 10, &ALL));
+
+        // No peek: only hit or stand against a ten when a dealer blackjack takes double stakes
+        let european = BasicStrategy::new(&Rules::european());
+        assert_eq!(Command::Hit, best(&european, &eleven, 10, &ALL));
+        assert_eq!(Command::Double, best(&s17, &eleven, 10, &ALL));
+    }
+
+    #[test]
+    fn advice_for_the_active_hand() {
+        // Seed 17 deals a hand the player can stand on
+        let mut app = App::new(100, Rules::default(), 17);
+        let strategy = BasicStrategy::new(&app.rules);
+        assert!(strategy.advise(&app, None).is_none());
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        let advice = strategy.advise(&app, None).unwrap();
+        assert!(app.legal_actions().contains(&advice.best()));
+        assert_eq!(Some(0.0), advice.cost(advice.best()));
+        assert!(advice
+            .plays
+            .iter()
+            .all(|(command, _)| advice.cost(*command) >= Some(0.0)));
+    }
+
+    #[test]
+    fn index_plays_follow_the_count() {
+        let deal = |rules: Rules, ranks: &[Rank]| {
+            let mut app = App::new(100, rules, 1);
+            app.shoe.stack(ranks);
+            app.place_bet(10).unwrap();
+            app.start().unwrap();
+            app
+        };
+        // 16 against a ten: stand from a true count of 0
+        let sixteen = [Rank::King, Rank::Queen, Rank::Pip(6), Rank::Pip(5)];
+        let app = deal(Rules::default(), &sixteen);
+        let strategy = BasicStrategy::new(&app.rules);
+        assert_eq!(Command::Hit, strategy.advise(&app, None).unwrap().best());
+        assert!(strategy
+            .advise(&app, Some(-0.5))
+            .unwrap()
+            .index_play
+            .is_none());
+        let advice = strategy.advise(&app, Some(0.5)).unwrap();
+        assert_eq!(Command::Stand, advice.best());
+        assert_eq!(
+            Some(IndexPlay {
+                play: Command::Stand,
+                index: 0.0,
+                true_count: 0.5
+            }),
+            advice.index_play
+        );
+        // Surrendering stays the better play where the table allows it
+        let app = deal(Rules::vegas_strip(), &sixteen);
+        let late = BasicStrategy::new(&app.rules);
+        assert_eq!(
+            Command::Surrender,
+            late.advise(&app, Some(2.0)).unwrap().best()
+        );
+
+        // Fab 4: don't surrender 15 against a ten below 0
+        let fifteen = [Rank::King, Rank::Queen, Rank::Pip(5), Rank::Pip(5)];
+        let app = deal(Rules::vegas_strip(), &fifteen);
+        assert_eq!(
+            Command::Surrender,
+            late.advise(&app, Some(0.0)).unwrap().best()
+        );
+        assert_eq!(Command::Hit, late.advise(&app, Some(-1.0)).unwrap().best());
+
+        // Insurance from a true count of 3
+        let twenty = [Rank::King, Rank::Ace, Rank::Queen, Rank::Pip(5)];
+        let app = deal(Rules::default(), &twenty);
+        assert_eq!(GameState::Insurance, app.state);
+        assert_eq!(
+            Command::Insurance(0),
+            strategy.advise(&app, Some(2.9)).unwrap().best()
+        );
+        let take = Command::Insurance(app.max_insurance());
+        assert_eq!(take, strategy.advise(&app, Some(3.0)).unwrap().best());
+        let no_insurance = strategy.clone().with_indices(IndexTable::fab_4());
+        assert_eq!(
+            DECLINE,
+            no_insurance.advise(&app, Some(9.0)).unwrap().best()
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use std::{error::Error, fmt::Display, path::Path};
+
+use serde::Deserialize;
+
+use crate::app::DEFAULT_BANK;
+use crate::file::{load_file, parse_json, parse_toml, FileError};
+use crate::rules::Rules;
+
+/// A table definition loaded from a TOML or JSON file: the house rules and the player's
+/// starting bank.
+///
+/// ```toml
+/// bank = 200
+///
+/// [rules]
+/// name = "Vegas Strip"
+/// decks = 6
+/// dealer_hits_soft_17 = false
+/// blackjack_payout = "3:2"
+/// surrender = "late"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Table {
+    pub bank: u32,
+    pub rules: Rules,
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Table {
+            bank: DEFAULT_BANK,
+            rules: Rules::default(),
+        }
+    }
+}
+
+impl Table {
+    /// Read and validate a table file
+    pub fn load(path: impl AsRef<Path>) -> Result<Table, TableError> {
+        let path = path.as_ref();
+        let table: Table = load_file(path).map_err(TableError::File)?;
+        table
+            .validate()
+            .map_err(|message| TableError::File(FileError::invalid(path, message)))?;
+        Ok(table)
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Table, TableError> {
+        let table: Table = parse_toml(contents).map_err(TableError::Parse)?;
+        table.validate().map_err(TableError::Invalid)?;
+        Ok(table)
+    }
+
+    pub fn from_json(contents: &str) -> Result<Table, TableError> {
+        let table: Table = parse_json(contents).map_err(TableError::Parse)?;
+        table.validate().map_err(TableError::Invalid)?;
+        Ok(table)
+    }
+
+    /// Rules that cannot be played together, or a bank too small for the table
+    fn validate(&self) -> Result<(), String> {
+        self.rules.validate()?;
+        if self.bank < self.rules.min_bet {
+            return Err(format!(
+                "bank (${}) does not cover min_bet (${})",
+                self.bank, self.rules.min_bet
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum TableError {
+    /// A table file could not be read, parsed or validated
+    File(FileError),
+    /// The contents are not valid TOML/JSON or have unknown or mistyped fields
+    Parse(String),
+    /// The contents parsed but describe rules that cannot be played together
+    Invalid(String),
+}
+
+impl Display for TableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableError::File(err) => write!(f, "Table {}", err),
+            TableError::Parse(message) => write!(f, "Invalid table: {}", message.trim()),
+            TableError::Invalid(message) => write!(f, "Impossible table rules: {}", message),
+        }
+    }
+}
+
+impl Error for TableError {}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+    use crate::rules::SurrenderRule;
+
+    #[test]
+    fn bundled_tables_match_presets() {
+        let tables = Path::new(env!("CARGO_MANIFEST_DIR")).join("tables");
+        let presets = [
+            ("vegas-strip.toml", Rules::vegas_strip()),
+            ("atlantic-city.toml", Rules::atlantic_city()),
+            ("downtown.toml", Rules::downtown()),
+            ("european.toml", Rules::european()),
+        ];
+        for (file, rules) in presets {
+            let table = Table::load(tables.join(file)).unwrap();
+            assert_eq!(rules, table.rules, "{}", file);
+        }
+    }
+
+    #[test]
+    fn bundled_tables_are_valid() {
+        let tables = Path::new(env!("CARGO_MANIFEST_DIR")).join("tables");
+        for entry in fs::read_dir(tables).unwrap() {
+            let path = entry.unwrap().path();
+            if let Err(err) = Table::load(&path) {
+                panic!("{}", err);
+            }
+        }
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let table = Table::from_toml("[rules]\nsurrender = \"early\"").unwrap();
+        assert_eq!(DEFAULT_BANK, table.bank);
+        assert_eq!(SurrenderRule::Early, table.rules.surrender);
+        assert_eq!(Rules::default().decks, table.rules.decks);
+
+        let table = Table::from_json(r#"{ "bank": 500, "rules": { "decks": 2 } }"#).unwrap();
+        assert_eq!(500, table.bank);
+        assert_eq!(2, table.rules.decks);
+    }
+
+    #[test]
+    fn rejects_bad_tables() {
+        let unknown = Table::from_toml("[rules]\ndeck = 2");
+        assert!(matches!(unknown, Err(TableError::Parse(_))));
+
+        let bad_payout = Table::from_toml("[rules]\nblackjack_payout = \"2:1\"");
+        assert!(matches!(bad_payout, Err(TableError::Parse(_))));
+
+        let limits = Table::from_toml("[rules]\nmin_bet = 50\nmax_bet = 25");
+        assert!(matches!(limits, Err(TableError::Invalid(_))));
+
+        let surrender = Table::from_toml("[rules]\nsurrender = \"late\"\nno_hole_card = true");
+        assert!(matches!(surrender, Err(TableError::Invalid(_))));
+
+        let bank = Table::from_toml("bank = 5\n[rules]\nmin_bet = 10");
+        assert!(matches!(bank, Err(TableError::Invalid(_))));
+    }
+}
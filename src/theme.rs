@@ -0,0 +1,178 @@
+use ratatui::style::Color;
+
+use tui_blackjack::cards::{Card, Suit};
+
+/// Names accepted by `Theme::by_name`
+pub const THEMES: [&str; 5] = [
+    "classic",
+    "four-colour",
+    "high-contrast",
+    "colour-blind",
+    "monochrome",
+];
+
+/// Colours used across the UI. Face-down cards are always drawn in `card_back`, whatever their
+/// suit, so the hole card gives nothing away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub title: Color,
+    /// Background of the dealer and player areas
+    pub table: Color,
+    /// Key hints, prompts and the active hand
+    pub accent: Color,
+    /// Moves that cannot be played right now
+    pub muted: Color,
+    pub win: Color,
+    pub lose: Color,
+    pub push: Color,
+    pub hearts: Color,
+    pub diamonds: Color,
+    pub spades: Color,
+    pub clubs: Color,
+    pub card_back: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::classic()
+    }
+}
+
+impl Theme {
+    /// Look up one of the named `THEMES`
+    pub fn by_name(name: &str) -> Option<Theme> {
+        match name {
+            "classic" => Some(Theme::classic()),
+            "four-colour" => Some(Theme::four_colour()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            "colour-blind" => Some(Theme::colour_blind()),
+            "monochrome" => Some(Theme::monochrome()),
+            _ => None,
+        }
+    }
+
+    /// Red hearts and diamonds, grey spades and clubs
+    pub fn classic() -> Theme {
+        Theme {
+            title: Color::Blue,
+            table: Color::DarkGray,
+            accent: Color::Yellow,
+            muted: Color::DarkGray,
+            win: Color::LightGreen,
+            lose: Color::LightRed,
+            push: Color::Blue,
+            hearts: Color::LightRed,
+            diamonds: Color::LightRed,
+            spades: Color::Gray,
+            clubs: Color::Gray,
+            card_back: Color::LightBlue,
+        }
+    }
+
+    /// A colour per suit so diamonds and hearts, spades and clubs are told apart at a glance
+    pub fn four_colour() -> Theme {
+        Theme {
+            diamonds: Color::LightBlue,
+            clubs: Color::LightGreen,
+            card_back: Color::Gray,
+            ..Theme::classic()
+        }
+    }
+
+    /// Bright colours on a black table
+    pub fn high_contrast() -> Theme {
+        Theme {
+            title: Color::White,
+            table: Color::Black,
+            accent: Color::LightYellow,
+            muted: Color::Gray,
+            win: Color::LightGreen,
+            lose: Color::LightRed,
+            push: Color::LightCyan,
+            hearts: Color::LightRed,
+            diamonds: Color::LightRed,
+            spades: Color::White,
+            clubs: Color::White,
+            card_back: Color::LightCyan,
+        }
+    }
+
+    /// Okabe-Ito colours, which stay distinct under red-green colour blindness. Wins and losses
+    /// are blue and vermilion rather than green and red.
+    pub fn colour_blind() -> Theme {
+        let blue = Color::Rgb(0, 114, 178);
+        let sky_blue = Color::Rgb(86, 180, 233);
+        let vermilion = Color::Rgb(213, 94, 0);
+        let yellow = Color::Rgb(240, 228, 66);
+        Theme {
+            title: sky_blue,
+            table: Color::DarkGray,
+            accent: yellow,
+            muted: Color::DarkGray,
+            win: blue,
+            lose: vermilion,
+            push: Color::White,
+            hearts: vermilion,
+            diamonds: yellow,
+            spades: Color::White,
+            clubs: sky_blue,
+            card_back: Color::Gray,
+        }
+    }
+
+    /// Shades of grey only
+    pub fn monochrome() -> Theme {
+        Theme {
+            title: Color::White,
+            table: Color::Reset,
+            accent: Color::White,
+            muted: Color::DarkGray,
+            win: Color::White,
+            lose: Color::White,
+            push: Color::White,
+            hearts: Color::White,
+            diamonds: Color::White,
+            spades: Color::White,
+            clubs: Color::White,
+            card_back: Color::Gray,
+        }
+    }
+
+    pub fn suit(&self, suit: Suit) -> Color {
+        match suit {
+            Suit::Hearts => self.hearts,
+            Suit::Diamonds => self.diamonds,
+            Suit::Spades => self.spades,
+            Suit::Clubs => self.clubs,
+        }
+    }
+
+    /// Colour of the suit for a face-up card, the neutral back colour for a face-down one
+    pub fn card(&self, card: &Card) -> Color {
+        if card.down {
+            self.card_back
+        } else {
+            self.suit(card.suit)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tui_blackjack::cards::{Rank, Suit};
+
+    #[test]
+    fn face_down_cards_are_neutral() {
+        for name in THEMES {
+            let theme = Theme::by_name(name).unwrap();
+            for suit in [Suit::Hearts, Suit::Diamonds, Suit::Spades, Suit::Clubs] {
+                let mut card = Card::new(Rank::Ace, suit);
+                assert_eq!(theme.suit(suit), theme.card(&card));
+                card.face_down();
+                assert_eq!(theme.card_back, theme.card(&card));
+            }
+        }
+        assert!(Theme::by_name("sepia").is_none());
+    }
+}
@@ -0,0 +1,142 @@
+use crate::app::{App, Command};
+use crate::strategy::{BasicStrategy, IndexPlay};
+
+/// A play that differs from basic strategy or the index play
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mistake {
+    pub played: Command,
+    pub correct: Command,
+    /// Expected value given up, in dollars at the hand's bet. Zero when the right play is an
+    /// index play, as the chart's values do not follow the count.
+    pub cost: f64,
+    /// Set when the right play came from the index table
+    pub index_play: Option<IndexPlay>,
+}
+
+/// Grades every play against basic strategy and keeps score for the session
+#[derive(Debug, Clone)]
+pub struct Trainer {
+    strategy: BasicStrategy,
+    /// Wrong plays are refused until the right one is made
+    pub block_mistakes: bool,
+    /// Plays graded where there was more than one legal choice
+    pub decisions: u32,
+    pub correct: u32,
+    /// Expected value given up over the session, in dollars
+    pub ev_lost: f64,
+    /// Length of the event stream at the last graded decision, so a retry after a blocked
+    /// mistake is not scored twice
+    graded_at: Option<usize>,
+}
+
+impl Trainer {
+    pub fn new(strategy: BasicStrategy, block_mistakes: bool) -> Trainer {
+        Trainer {
+            strategy,
+            block_mistakes,
+            decisions: 0,
+            correct: 0,
+            ev_lost: 0.0,
+            graded_at: None,
+        }
+    }
+
+    pub fn strategy(&self) -> &BasicStrategy {
+        &self.strategy
+    }
+
+    /// Compare a play on the active hand, or an insurance decision, with basic strategy before it
+    /// is made. With a true count, index plays are expected where the count calls for them. Only
+    /// the first attempt at each decision counts towards the score.
+    pub fn grade(
+        &mut self,
+        app: &App,
+        command: Command,
+        true_count: Option<f32>,
+    ) -> Option<Mistake> {
+        let advice = self.strategy.advise(app, true_count)?;
+        if advice.plays.len() < 2 {
+            return None;
+        }
+        // Any stake counts as taking insurance
+        let command = match command {
+            Command::Insurance(stake) if stake > 0 => Command::Insurance(app.max_insurance()),
+            _ => command,
+        };
+        let cost = match advice.index_play {
+            Some(_) => 0.0,
+            None => advice.cost(command)? * app.player_hand()?.bet as f64,
+        };
+        let mistake = (command != advice.best()).then_some(Mistake {
+            played: command,
+            correct: advice.best(),
+            cost,
+            index_play: advice.index_play,
+        });
+
+        let decision = app.events().len();
+        if self.graded_at != Some(decision) {
+            self.graded_at = Some(decision);
+            self.decisions += 1;
+            match mistake {
+                Some(mistake) => self.ev_lost += mistake.cost,
+                None => self.correct += 1,
+            }
+        }
+        mistake
+    }
+
+    /// Share of decisions played correctly, as a percentage
+    pub fn accuracy(&self) -> Option<f64> {
+        (self.decisions > 0).then(|| self.correct as f64 * 100.0 / self.decisions as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cards::Rank;
+    use crate::rules::Rules;
+
+    #[test]
+    fn grades_each_decision_once() {
+        // Seed 17 deals a hard 17 against a queen
+        let mut app = App::new(100, Rules::default(), 17);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        let mut trainer = Trainer::new(BasicStrategy::new(&app.rules), true);
+        assert_eq!(None, trainer.accuracy());
+
+        let mistake = trainer.grade(&app, Command::Hit, None).unwrap();
+        assert_eq!(Command::Stand, mistake.correct);
+        assert!(mistake.cost > 0.0);
+        // Retrying the blocked decision does not count again
+        assert_eq!(None, trainer.grade(&app, Command::Stand, None));
+        assert_eq!((1, 0), (trainer.decisions, trainer.correct));
+        assert_eq!(mistake.cost, trainer.ev_lost);
+        assert_eq!(Some(0.0), trainer.accuracy());
+
+        let mut trainer = Trainer::new(BasicStrategy::new(&app.rules), false);
+        assert_eq!(None, trainer.grade(&app, Command::Stand, None));
+        assert_eq!(Some(100.0), trainer.accuracy());
+    }
+
+    #[test]
+    fn grades_index_plays() {
+        // 16 against a ten: stand from a true count of 0
+        let mut app = App::new(100, Rules::default(), 17);
+        app.shoe
+            .stack(&[Rank::King, Rank::Queen, Rank::Pip(6), Rank::Pip(5)]);
+        app.place_bet(10).unwrap();
+        app.start().unwrap();
+        let mut trainer = Trainer::new(BasicStrategy::new(&app.rules), false);
+        assert_eq!(None, trainer.grade(&app, Command::Hit, Some(-1.0)));
+        let mistake = trainer.grade(&app, Command::Hit, Some(1.0)).unwrap();
+        assert_eq!(Command::Stand, mistake.correct);
+        assert_eq!(
+            Some(0.0),
+            mistake.index_play.map(|index_play| index_play.index)
+        );
+        assert_eq!(0.0, mistake.cost);
+    }
+}
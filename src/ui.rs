@@ -1,16 +1,60 @@
+use std::rc::Rc;
+
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Style, Stylize},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    style::{Style, Stylize},
     text::{Line, Span},
     widgets::{Block, Borders, Clear, Paragraph},
     Frame,
 };
 use tui_textarea::TextArea;
 
-use crate::app::*;
-use crate::cards::{Card, Suit};
+use tui_blackjack::app::*;
+use tui_blackjack::cards::Card;
+use tui_blackjack::count::decks_left;
+use tui_blackjack::event::Seat;
+use tui_blackjack::hand::HandValue;
+use tui_blackjack::strategy::{Advice, IndexPlay};
+use tui_blackjack::trainer::Mistake;
+
+use crate::animation::{Animation, Animator};
+use crate::card_view::{fan, render_card};
+use crate::practice::Practice;
+use crate::theme::Theme;
+
+const SHOE_LABEL: &str = "[Shoe]";
+
+/// Keys for the moves on the player's turn, in the order they are hinted
+pub const PLAYER_KEYS: [(char, Command, &str); 5] = [
+    ('h', Command::Hit, "hit"),
+    ('s', Command::Stand, "stand"),
+    ('d', Command::Double, "double"),
+    ('p', Command::Split, "split"),
+    ('r', Command::Surrender, "surrender"),
+];
+
+/// Message shown in the command bar in place of the key hints until the next key press
+pub enum Notice {
+    /// The engine refused a bet or command
+    Refused(GameError),
+    /// Basic strategy's play for the decision at hand, or the index play when the count calls
+    /// for one
+    Hint(Advice),
+    /// Trainer mode caught a play that differs from basic strategy
+    Mistake(Mistake),
+    /// The player's answer to a running count quiz
+    Quiz { guess: f32, count: f32 },
+}
 
-pub fn ui(f: &mut Frame, app: &App, form: &mut TextArea) {
+pub fn ui(
+    f: &mut Frame,
+    app: &App,
+    form: &mut TextArea,
+    notice: Option<&Notice>,
+    animator: &Animator,
+    practice: &Practice,
+    theme: &Theme,
+) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -40,15 +84,17 @@ pub fn ui(f: &mut Frame, app: &App, form: &mut TextArea) {
 
     let player_cards_rect = centered_rect(75, 75, player_chunks[1]);
     let player_stats_rect = centered_rect(75, 75, player_chunks[0]);
+    let player_side_rect = centered_rect(75, 75, player_chunks[2]);
 
     // Title bar
     let title_block = Block::default()
         .borders(Borders::ALL)
+        .title_bottom(Line::from(app.rules.summary()).centered())
         .style(Style::default());
 
     let title = Paragraph::new(
         Line::from("COMMAND LINE BLACKJACK")
-            .fg(Color::Blue)
+            .fg(theme.title)
             .centered()
             .bold(),
     )
@@ -56,18 +102,88 @@ pub fn ui(f: &mut Frame, app: &App, form: &mut TextArea) {
 
     f.render_widget(title, title_rect);
 
-    let command_hint = {
-        match app.state {
-            GameState::EnterBet => "<Enter> to place bet / <Escape> to quit game",
-            GameState::PlayerTurn => "<h> to hit / <s> to stand / <q> to quit game",
-            GameState::DealerTurn => "Dealer's play...",
-            _ => "<Enter> to play again / <q> to quit",
+    let legal = app.legal_actions();
+    let command_hint = match app.state {
+        GameState::EnterBet => Line::from("<Enter> to place bet / <Escape> to quit game"),
+        GameState::Insurance if app.player_hand().is_some_and(Hand::is_natural) => {
+            let even_money = legal.contains(&Command::EvenMoney);
+            let mut hint = hint_line(
+                theme,
+                vec![
+                    ("<y> to take 1:1", even_money),
+                    ("<n> to play on", true),
+                    ("<?> for a hint", true),
+                ],
+            );
+            hint.spans.insert(0, Span::raw("Even money? "));
+            hint
         }
+        GameState::Insurance => {
+            let can_insure = legal
+                .iter()
+                .any(|command| matches!(command, Command::Insurance(stake) if *stake > 0));
+            let mut hint = hint_line(
+                theme,
+                vec![
+                    ("<Enter> to insure", can_insure),
+                    ("<Esc> to decline", true),
+                    ("<?> for a hint", true),
+                ],
+            );
+            hint.spans.insert(0, Span::raw("Dealer shows an ace: "));
+            hint
+        }
+        GameState::PlayerTurn => player_turn_hint(&legal, theme),
+        GameState::DealerTurn => Line::from("Dealer's play..."),
+        _ => Line::from("<Enter> to play again / <q> to quit"),
     };
 
-    let command_hint = Span::styled(command_hint, Style::default().fg(Color::Yellow));
-    let command_hint =
-        Paragraph::new(Line::from(command_hint).centered().bold()).block(Block::default());
+    let command_hint = match notice {
+        Some(Notice::Refused(err)) => Line::from(err.to_string()).fg(theme.lose),
+        Some(Notice::Hint(advice)) => {
+            let hand = format!(
+                "{} on {} against {}",
+                advice.best(),
+                app.player_value(),
+                app.dealer_hand[0].rank.label()
+            );
+            let hint = match advice.index_play {
+                Some(index_play) => format!("Index play: {}{}", hand, index_detail(&index_play)),
+                None => format!("Basic strategy: {}", hand),
+            };
+            Line::from(hint).fg(theme.accent)
+        }
+        Some(Notice::Mistake(mistake)) => {
+            let blocked = practice
+                .trainer
+                .as_ref()
+                .is_some_and(|trainer| trainer.block_mistakes);
+            let plays = format!("to {}, not {}", mistake.correct, mistake.played);
+            let mistake = match mistake.index_play {
+                Some(index_play) => {
+                    format!("The index play is {}{}", plays, index_detail(&index_play))
+                }
+                None => format!(
+                    "Basic strategy is {} (costs ${:.2} in EV)",
+                    plays, mistake.cost
+                ),
+            };
+            Line::from(format!(
+                "{}{}",
+                mistake,
+                if blocked { ". Try again" } else { "" }
+            ))
+            .fg(theme.lose)
+        }
+        Some(Notice::Quiz { guess, count }) if guess == count => {
+            Line::from(format!("Right, the running count is {:+}", count)).fg(theme.win)
+        }
+        Some(Notice::Quiz { guess, count }) => {
+            Line::from(format!("The running count is {:+}, not {:+}", count, guess)).fg(theme.lose)
+        }
+        None => command_hint.fg(theme.accent),
+    };
+    let command_hint = Paragraph::new(command_hint.centered().bold()).block(Block::default());
 
     f.render_widget(command_hint, command_rect);
 
@@ -75,83 +191,296 @@ pub fn ui(f: &mut Frame, app: &App, form: &mut TextArea) {
         .title("Dealer")
         .borders(Borders::ALL)
         .title_alignment(Alignment::Center)
-        .style(Style::default().bg(Color::DarkGray));
+        .style(Style::default().bg(theme.table));
     f.render_widget(dealer_block, dealer_rect);
 
     let player_block = Block::default()
         .title("Player")
         .borders(Borders::ALL)
         .title_alignment(Alignment::Center)
-        .style(Style::default().bg(Color::DarkGray));
+        .style(Style::default().bg(theme.table));
     f.render_widget(player_block, player_rect);
 
-    render_player_stats(f, app, player_stats_rect);
+    let shoe_rect = Rect {
+        x: dealer_rect
+            .right()
+            .saturating_sub(SHOE_LABEL.len() as u16 + 2),
+        y: dealer_rect.y + 1,
+        width: SHOE_LABEL.len() as u16,
+        height: 1,
+    }
+    .intersection(dealer_rect);
+    f.render_widget(Paragraph::new(SHOE_LABEL).fg(theme.card_back), shoe_rect);
+
+    render_player_stats(f, app, animator, practice, player_stats_rect, theme);
+    // Results wait until the cards and chips have landed
+    let settled = animator.is_idle();
 
     match app.state {
         GameState::EnterBet => {
             let bet_rect = centered_rect(100, 25, player_cards_rect);
             let bet_form = form.widget();
             f.render_widget(bet_form, bet_rect);
+            if app.shuffled {
+                let shuffle_rect = centered_rect(100, 25, dealer_cards_rect);
+                let shuffle_text = Paragraph::new(vec![
+                    Line::from("Shuffling\u{2026}").fg(theme.accent).bold(),
+                    Line::from("The shoe was reshuffled last round"),
+                ])
+                .centered();
+                f.render_widget(shuffle_text, shuffle_rect);
+            }
+        }
+        GameState::Insurance => {
+            render_player_cards(f, app, animator, player_cards_rect, theme);
+            render_dealer_cards(f, app, animator, dealer_cards_rect, theme);
+            if !app.player_hand().is_some_and(Hand::is_natural) {
+                let insurance_rect = centered_rect(100, 25, player_side_rect);
+                f.render_widget(form.widget(), insurance_rect);
+            }
         }
         GameState::PlayerTurn => {
-            render_player_cards(f, app, player_cards_rect);
-            render_dealer_cards(f, app, dealer_cards_rect);
+            render_player_cards(f, app, animator, player_cards_rect, theme);
+            render_dealer_cards(f, app, animator, dealer_cards_rect, theme);
         }
         GameState::DealerTurn => {
-            render_player_cards(f, app, player_cards_rect);
-            render_dealer_cards(f, app, dealer_cards_rect);
+            render_player_cards(f, app, animator, player_cards_rect, theme);
+            render_dealer_cards(f, app, animator, dealer_cards_rect, theme);
         }
         GameState::Win => {
             let win_text = Paragraph::new(vec![
-                Line::from(format!("You win! +${}", app.current_bet)).fg(Color::LightGreen).bold(),
+                Line::from(format!("You win! +${}", app.net_result()))
+                    .fg(theme.win)
+                    .bold(),
                 Line::from(""),
-                Line::from("Press <Enter> to play again / <q> to quit").fg(Color::Yellow),
+                Line::from("Press <Enter> to play again / <q> to quit").fg(theme.accent),
             ])
             .centered();
-            render_player_cards(f, app, player_cards_rect);
-            render_dealer_cards(f, app, dealer_cards_rect);
-            f.render_widget(Clear, command_rect);
-            f.render_widget(win_text, command_rect);
+            render_player_cards(f, app, animator, player_cards_rect, theme);
+            render_dealer_cards(f, app, animator, dealer_cards_rect, theme);
+            if settled {
+                f.render_widget(Clear, command_rect);
+                f.render_widget(win_text, command_rect);
+            }
         }
         GameState::Lose => {
             let lose_text = Paragraph::new(vec![
-                Line::from(format!("Better luck next time. -${}", app.current_bet))
-                    .fg(Color::LightRed)
+                Line::from(format!("Better luck next time. -${}", -app.net_result()))
+                    .fg(theme.lose)
                     .bold(),
                 Line::from(""),
-                Line::from("Press <Enter> to play again / <q> to quit").fg(Color::Yellow),
+                Line::from("Press <Enter> to play again / <q> to quit").fg(theme.accent),
             ])
             .centered();
-            render_player_cards(f, app, player_cards_rect);
-            render_dealer_cards(f, app, dealer_cards_rect);
-            f.render_widget(Clear, command_rect);
-            f.render_widget(lose_text, command_rect);
+            render_player_cards(f, app, animator, player_cards_rect, theme);
+            render_dealer_cards(f, app, animator, dealer_cards_rect, theme);
+            if settled {
+                f.render_widget(Clear, command_rect);
+                f.render_widget(lose_text, command_rect);
+            }
         }
         GameState::Blackjack => {
             let win_text = Paragraph::new(vec![
-                Line::from(format!("Blackjack! +${}", app.blackjack_payout)).fg(Color::LightGreen).bold(),
+                Line::from(format!("Blackjack! {}", signed_dollars(app.net_result())))
+                    .fg(theme.win)
+                    .bold(),
                 Line::from(""),
-                Line::from("Press <Enter> to play again / <q> to quit").fg(Color::Yellow),
+                Line::from("Press <Enter> to play again / <q> to quit").fg(theme.accent),
             ])
             .centered();
-            render_player_cards(f, app, player_cards_rect);
-            render_dealer_cards(f, app, dealer_cards_rect);
-            f.render_widget(Clear, command_rect);
-            f.render_widget(win_text, command_rect);
+            render_player_cards(f, app, animator, player_cards_rect, theme);
+            render_dealer_cards(f, app, animator, dealer_cards_rect, theme);
+            if settled {
+                f.render_widget(Clear, command_rect);
+                f.render_widget(win_text, command_rect);
+            }
         }
         GameState::Draw => {
             let draw_text = Paragraph::new(vec![
-                Line::from("Draw!").fg(Color::Blue).bold(),
+                Line::from("Draw!").fg(theme.push).bold(),
                 Line::from(""),
-                Line::from("Press <Enter> to play again / <q> to quit").fg(Color::Yellow),
+                Line::from("Press <Enter> to play again / <q> to quit").fg(theme.accent),
             ])
             .centered();
-            render_player_cards(f, app, player_cards_rect);
-            render_dealer_cards(f, app, dealer_cards_rect);
-            f.render_widget(Clear, command_rect);
-            f.render_widget(draw_text, command_rect);
+            render_player_cards(f, app, animator, player_cards_rect, theme);
+            render_dealer_cards(f, app, animator, dealer_cards_rect, theme);
+            if settled {
+                f.render_widget(Clear, command_rect);
+                f.render_widget(draw_text, command_rect);
+            }
+        }
+        GameState::Surrender => {
+            let surrender_text = Paragraph::new(vec![
+                Line::from(format!(
+                    "{} (surrendered)",
+                    signed_dollars(app.net_result())
+                ))
+                .fg(theme.lose)
+                .bold(),
+                Line::from(""),
+                Line::from("Press <Enter> to play again / <q> to quit").fg(theme.accent),
+            ])
+            .centered();
+            render_player_cards(f, app, animator, player_cards_rect, theme);
+            render_dealer_cards(f, app, animator, dealer_cards_rect, theme);
+            if settled {
+                f.render_widget(Clear, command_rect);
+                f.render_widget(surrender_text, command_rect);
+            }
+        }
+    }
+
+    let areas = AnimationAreas {
+        shoe: shoe_rect,
+        dealer: dealer_cards_rect,
+        player: player_cards_rect,
+        stats: player_stats_rect,
+    };
+    if let Some(quiz) = &practice.quiz {
+        f.render_widget(Clear, command_rect);
+        f.render_widget(quiz.widget(), centered_rect(40, 100, command_rect));
+    }
+
+    render_animation(f, app, animator, areas, theme);
+}
+
+/// Parts of the table that cards and chips move between
+struct AnimationAreas {
+    shoe: Rect,
+    dealer: Rect,
+    player: Rect,
+    stats: Rect,
+}
+
+/// Draw the card or chips currently moving across the table on top of everything else
+fn render_animation(
+    f: &mut Frame,
+    app: &App,
+    animator: &Animator,
+    areas: AnimationAreas,
+    theme: &Theme,
+) {
+    let AnimationAreas {
+        shoe: shoe_rect,
+        dealer: dealer_rect,
+        player: player_rect,
+        stats: stats_rect,
+    } = areas;
+    let Some((animation, progress)) = animator.current() else {
+        return;
+    };
+    // Ease out so things slow down as they land
+    let progress = 1.0 - (1.0 - progress).powi(2);
+    let lerp = |a: u16, b: u16| (a as f32 + (b as f32 - a as f32) * progress).round() as u16;
+    let (line, from, to) = match animation {
+        Animation::Deal { to, card } => {
+            let (rect, landed) = match *to {
+                Seat::Dealer => (
+                    dealer_rect,
+                    app.dealer_hand.len() - animator.cards_in_flight(*to),
+                ),
+                Seat::Player(index) => {
+                    let Some(&rect) = hand_rects(app, player_rect).get(index) else {
+                        return;
+                    };
+                    let landed = app.hands[index].cards.len() - animator.cards_in_flight(*to);
+                    (rect, landed)
+                }
+            };
+            let inner = rect.inner(Margin::new(1, 1));
+            let from = (shoe_rect.x, shoe_rect.y);
+            // Slide a large card into the fan when the hand is drawn large
+            if let Some(slots) = fan(inner, landed + 1) {
+                let slot = slots[landed];
+                let moving = Rect {
+                    x: lerp(from.0, slot.x),
+                    y: lerp(from.1, slot.y),
+                    ..slot
+                };
+                render_card(f, moving, card, card_style(card, theme), 1.0);
+                return;
+            }
+            let line = display_card(card, theme).alignment(Alignment::Left);
+            let width = line.width() as u16;
+            let slot = (
+                inner.x + inner.width.saturating_sub(width) / 2,
+                inner.y + landed as u16,
+            );
+            (line, from, slot)
+        }
+        Animation::Chips { change } => {
+            let (from, to, line) = if *change > 0 {
+                let line = Line::from(format!("+${}", change)).fg(theme.win);
+                let bank = stats_rect.inner(Margin::new(1, 1));
+                (centre(dealer_rect), (bank.x, bank.y), line)
+            } else {
+                let line = Line::from(format!("-${}", -change)).fg(theme.lose);
+                (centre(player_rect), centre(dealer_rect), line)
+            };
+            (line.bold(), from, to)
+        }
+        // The flip is drawn in place by render_dealer_cards
+        Animation::Flip { .. } => return,
+    };
+    let rect = Rect {
+        x: lerp(from.0, to.0),
+        y: lerp(from.1, to.1),
+        width: line.width() as u16,
+        height: 1,
+    }
+    .intersection(f.size());
+    f.render_widget(Clear, rect);
+    f.render_widget(Paragraph::new(line), rect);
+}
+
+fn centre(rect: Rect) -> (u16, u16) {
+    (rect.x + rect.width / 2, rect.y + rect.height / 2)
+}
+
+/// List every move on the player's turn, greying out those not legal on the active hand
+fn player_turn_hint(legal: &[Command], theme: &Theme) -> Line<'static> {
+    let hints: Vec<(String, bool)> = PLAYER_KEYS
+        .iter()
+        .map(|(key, command, name)| (format!("<{}> to {}", key, name), legal.contains(command)))
+        .collect();
+    let mut hint = hint_line(theme, hints);
+    hint.spans
+        .push(Span::raw(" / <?> for a hint / <q> to quit game"));
+    hint
+}
+
+/// A round's net result as "+$5" or "-$5", insurance included
+fn signed_dollars(net: i64) -> String {
+    if net < 0 {
+        format!("-${}", -net)
+    } else {
+        format!("+${}", net)
+    }
+}
+
+/// Where the count stands against the index of an index play
+fn index_detail(index_play: &IndexPlay) -> String {
+    format!(
+        " (true count {:+.1}, index {:+})",
+        index_play.true_count, index_play.index
+    )
+}
+
+/// Join key hints with " / ", greying out the unavailable ones
+fn hint_line(theme: &Theme, hints: Vec<(impl Into<String>, bool)>) -> Line<'static> {
+    let mut spans = Vec::new();
+    for (i, (hint, available)) in hints.into_iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw(" / "));
         }
+        let hint = Span::raw(hint.into());
+        spans.push(if available {
+            hint
+        } else {
+            hint.fg(theme.muted)
+        });
     }
+    Line::from(spans)
 }
 
 /// helper function to create a centered rect using up certain percentage of the available rect `r`
@@ -177,58 +506,230 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1] // Return the middle chunk
 }
 
-fn render_player_cards(f: &mut Frame, app: &App, rect: Rect) {
-    let block = Block::default()
-        .title("Current hand")
-        .borders(Borders::ALL)
-        .title_bottom(format!("Score: {}", app.player_score()))
-        .title_alignment(Alignment::Center);
-    let cards: Vec<Line> = app
-        .player_hand
-        .iter()
-        .map(|card| display_card(card))
-        .collect();
-    let card_view = Paragraph::new(cards).block(block);
-    f.render_widget(card_view, rect);
+/// Split the player's area between their hands
+fn hand_rects(app: &App, rect: Rect) -> Rc<[Rect]> {
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![
+            Constraint::Ratio(1, app.hands.len() as u32);
+            app.hands.len()
+        ])
+        .split(rect)
+}
+
+fn render_player_cards(f: &mut Frame, app: &App, animator: &Animator, rect: Rect, theme: &Theme) {
+    let hand_rects = hand_rects(app, rect);
+
+    for (i, (hand, &hand_rect)) in app.hands.iter().zip(hand_rects.iter()).enumerate() {
+        // Cards still sliding in from the shoe are drawn by the animation
+        let landed = hand.cards.len() - animator.cards_in_flight(Seat::Player(i));
+        let value = if landed == hand.cards.len() {
+            hand.value()
+        } else {
+            HandValue::of(&hand.cards[..landed])
+        };
+        let title = if app.hands.len() > 1 {
+            format!("Hand {} (${})", i + 1, hand.bet)
+        } else {
+            String::from("Current hand")
+        };
+        let outcome = hand.outcome.filter(|_| animator.is_idle());
+        let footer = match outcome {
+            Some(Outcome::Win) => String::from("Win"),
+            Some(Outcome::Lose) => String::from("Lose"),
+            Some(Outcome::Push) => String::from("Push"),
+            Some(Outcome::Blackjack) => String::from("Blackjack"),
+            Some(Outcome::Surrender) => String::from("Surrendered"),
+            None => format!("Score: {}", value),
+        };
+        let mut block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .title_bottom(footer)
+            .title_alignment(Alignment::Center);
+        if i == app.active_hand && app.hands.len() > 1 && matches!(app.state, GameState::PlayerTurn)
+        {
+            block = block.border_style(Style::default().fg(theme.accent).bold());
+        }
+        let cards_rect = block.inner(hand_rect);
+        f.render_widget(block, hand_rect);
+        render_cards(f, cards_rect, &hand.cards[..landed], None, theme);
+    }
 }
 
-fn render_dealer_cards(f: &mut Frame, app: &App, rect: Rect) {
+fn render_dealer_cards(f: &mut Frame, app: &App, animator: &Animator, rect: Rect, theme: &Theme) {
+    let landed = &app.dealer_hand[..app.dealer_hand.len() - animator.cards_in_flight(Seat::Dealer)];
+    let hole_card_hidden = app.hole_card_hidden() || animator.hole_card_hidden();
     let mut block = Block::default()
         .title("Current hand")
         .borders(Borders::ALL)
         .title_alignment(Alignment::Center);
-    if app.dealer_hand[0].down {
+    if hole_card_hidden || landed.len() < 2 {
         block = block.title_bottom(format!("Showing: {}", app.dealer_showing()));
     } else {
-        block = block.title_bottom(format!("Score: {}", app.dealer_score()));
+        block = block.title_bottom(format!("Score: {}", HandValue::of(landed)));
     }
-    let cards: Vec<Line> = app
-        .dealer_hand
-        .iter()
-        .map(|card| display_card(card))
-        .collect();
-    let card_view = Paragraph::new(cards).block(block);
-    f.render_widget(card_view, rect);
+    let flip = match animator.current() {
+        Some((Animation::Flip { .. }, progress)) => Some((1, progress)),
+        _ => None,
+    };
+    let mut cards = landed.to_vec();
+    if let (Some(hole_card), None) = (cards.get_mut(1), flip) {
+        if hole_card_hidden {
+            hole_card.face_down();
+        }
+    }
+    let cards_rect = block.inner(rect);
+    f.render_widget(block, rect);
+    render_cards(f, cards_rect, &cards, flip, theme);
 }
 
-fn render_player_stats(f: &mut Frame, app: &App, rect: Rect) {
+/// Draw a hand as large overlapping cards when they fit, or one line per card otherwise. `flip`
+/// gives the index of a card being turned over and how far it has turned.
+fn render_cards(
+    f: &mut Frame,
+    area: Rect,
+    cards: &[Card],
+    flip: Option<(usize, f32)>,
+    theme: &Theme,
+) {
+    let Some(slots) = fan(area, cards.len()) else {
+        let lines: Vec<Line> = cards
+            .iter()
+            .enumerate()
+            .map(|(i, card)| match flip {
+                Some((index, progress)) if index == i => flipping_card(card, progress, theme),
+                _ => display_card(card, theme),
+            })
+            .collect();
+        f.render_widget(Paragraph::new(lines), area);
+        return;
+    };
+    for (i, (card, slot)) in cards.iter().zip(slots).enumerate() {
+        match flip {
+            Some((index, progress)) if index == i => {
+                let mut shown = card.clone();
+                if progress < 0.5 {
+                    shown.face_down();
+                }
+                let width = (1.0 - 2.0 * progress).abs();
+                render_card(f, slot, &shown, card_style(&shown, theme), width);
+            }
+            _ => render_card(f, slot, card, card_style(card, theme), 1.0),
+        }
+    }
+}
+
+fn render_player_stats(
+    f: &mut Frame,
+    app: &App,
+    animator: &Animator,
+    practice: &Practice,
+    rect: Rect,
+    theme: &Theme,
+) {
     let block = Block::default()
         .title("Player stats")
         .borders(Borders::ALL)
         .title_alignment(Alignment::Center);
-    let stats = Paragraph::new(vec![
-        Line::from(format!("Bank: {}", app.bank)),
-        Line::from(format!("Current bet: {}", app.current_bet)),
-    ])
-    .block(block);
+    let mut lines = vec![
+        Line::from(format!(
+            "Bank: {}",
+            app.bank as i64 - animator.bank_in_flight()
+        )),
+        Line::from(format!(
+            "Current bet: {}",
+            app.total_bet().max(app.current_bet)
+        )),
+        Line::from(""),
+        Line::from(format!(
+            "Shoe: {}/{} cards",
+            app.shoe.remaining(),
+            app.shoe.size()
+        )),
+        Line::from(format!(
+            "Dealt: {}% (cut at {}%)",
+            app.shoe.dealt() * 100 / app.shoe.size(),
+            app.shoe.penetration()
+        )),
+        Line::from(format!("Seed: {}", app.shoe.seed())),
+    ];
+    // Training scores go straight under the bet, followed by any insurance
+    let mut training = Vec::new();
+    if let Some(trainer) = &practice.trainer {
+        let accuracy = match trainer.accuracy() {
+            Some(accuracy) => format!(
+                "Accuracy: {}/{} ({:.0}%)",
+                trainer.correct, trainer.decisions, accuracy
+            ),
+            None => String::from("Accuracy: -"),
+        };
+        training.push(Line::from(accuracy));
+        training.push(Line::from(format!("EV lost: ${:.2}", trainer.ev_lost)));
+    }
+    if let Some(counter) = &practice.counter {
+        if practice.count_shown() {
+            training.push(Line::from(format!("System: {}", counter.system().name())));
+            training.push(Line::from(format!("Running count: {:+}", counter.running)));
+            if let Some(true_count) = counter.true_count(&app.shoe) {
+                training.push(Line::from(format!("True count: {:+.1}", true_count)));
+            }
+            if let Some((left, surplus)) = counter.aces_left(&app.shoe) {
+                training.push(Line::from(format!("Aces left: {} ({:+.1})", left, surplus)));
+            }
+            training.push(Line::from(format!(
+                "Decks left: {:.1}",
+                decks_left(&app.shoe)
+            )));
+        } else if practice.quiz.is_some() {
+            training.push(Line::from("Count hidden").fg(theme.muted));
+        } else {
+            training.push(Line::from("<Tab> shows count").fg(theme.muted));
+        }
+        if counter.quizzes > 0 {
+            training.push(Line::from(format!(
+                "Quizzes: {}/{}",
+                counter.quizzes_right, counter.quizzes
+            )));
+        }
+    }
+    if app.insurance > 0 {
+        training.push(Line::from(format!("Insurance: {}", app.insurance)));
+        let result = match app.insurance_outcome {
+            Some(Outcome::Win) => {
+                Line::from(format!("Insurance won +${}", app.insurance_result())).fg(theme.win)
+            }
+            Some(_) => Line::from(format!("Insurance lost -${}", app.insurance)).fg(theme.lose),
+            None => Line::from("Insurance pending"),
+        };
+        training.push(result);
+    }
+    lines.splice(2..2, training);
+    let stats = Paragraph::new(lines).block(block);
     f.render_widget(stats, rect);
 }
 
-fn display_card(card: &Card) -> Line {
-    let color = match card.suit {
-        Suit::Hearts if !card.down => Color::LightRed,
-        Suit::Diamonds => Color::LightRed,
-        _ => Color::Gray,
-    };
-    Line::from(format!("{}", card)).fg(color).bold().centered()
+fn card_style(card: &Card, theme: &Theme) -> Style {
+    Style::default().fg(theme.card(card)).bold()
+}
+
+fn display_card(card: &Card, theme: &Theme) -> Line<'static> {
+    Line::from(format!("{}", card))
+        .style(card_style(card, theme))
+        .centered()
+}
+
+/// The hole card part way through turning over: the back narrows to an edge, then the face widens
+fn flipping_card(card: &Card, progress: f32, theme: &Theme) -> Line<'static> {
+    let mut shown = card.clone();
+    if progress < 0.5 {
+        shown.face_down();
+    }
+    let text: Vec<char> = shown.to_string().chars().collect();
+    let width = (text.len() as f32 * (1.0 - 2.0 * progress).abs()).ceil() as usize;
+    let width = width.clamp(1, text.len());
+    let skip = (text.len() - width) / 2;
+    Line::from(text[skip..skip + width].iter().collect::<String>())
+        .style(card_style(&shown, theme))
+        .centered()
 }